@@ -3,15 +3,16 @@ use std::sync::Arc;
 use crate::{
     interpreter::{
         functions::{AsyncMethod, FunctionDef, FunctionParam, SyncMethod},
+        proxy,
         types::{HashableIndexMap, MULTISEND_TRANSACTION_TYPE},
-        ContractInfo, Env, Type, Value,
+        utils, ContractInfo, Env, Type, Value,
     },
     loaders,
 };
 use alloy::{
     dyn_abi::{DynSolType, DynSolValue, JsonAbiExt},
     json_abi::{self, JsonAbi},
-    primitives::FixedBytes,
+    primitives::{Address, FixedBytes},
 };
 use anyhow::{anyhow, bail, Result};
 use futures::{future::BoxFuture, FutureExt};
@@ -98,11 +99,12 @@ fn abi_decode_data<'a>(
             bail!("abi.decodeData expects at least 4 bytes");
         }
         let selector = alloy::primitives::FixedBytes::<4>::from_slice(&data[..4]);
+        let client = env.get_signatures_client();
         let (signature, decoded) = if let Some(func) = env.get_function(&selector) {
             (func.signature(), func.abi_decode_input(&data[4..])?)
-        } else if let Some(error) = env.get_error(&selector) {
-            (error.signature(), error.abi_decode_input(&data[4..])?)
-        } else if let Ok(func) = loaders::four_bytes::find_function(selector).await {
+        } else if env.get_error(&selector).is_some() {
+            return utils::decode_error(env, data);
+        } else if let Ok(func) = loaders::four_bytes::find_function(&client, selector).await {
             env.register_function(func.clone());
             (func.signature(), func.abi_decode_input(&data[4..])?)
         } else {
@@ -117,8 +119,13 @@ fn abi_decode_calldata(_env: &mut Env, receiver: &Value, args: &[Value]) -> Resu
     _generic_abi_decode(receiver, args, "function", |abi| abi.functions().collect())
 }
 
-fn abi_decode_error(_env: &mut Env, receiver: &Value, args: &[Value]) -> Result<Value> {
-    _generic_abi_decode(receiver, args, "error", |abi| abi.errors().collect())
+fn abi_decode_error(env: &mut Env, receiver: &Value, args: &[Value]) -> Result<Value> {
+    _generic_abi_decode(receiver, args, "error", |abi| abi.errors().collect()).or_else(|e| {
+        match args.first() {
+            Some(Value::Bytes(data)) => utils::decode_error(env, data).map_err(|_| e),
+            _ => Err(e),
+        }
+    })
 }
 
 fn value_to_soltype(value: &Value) -> Result<DynSolType> {
@@ -176,6 +183,29 @@ fn abi_encode_packed_(_env: &mut Env, _receiver: &Value, args: &[Value]) -> Resu
     abi_encode_packed(args)
 }
 
+async fn fetch_contract_abi(
+    env: &mut Env,
+    name: &str,
+    address: Address,
+    merge_implementation: bool,
+) -> Result<Value> {
+    let chain_id = env.get_chain_id().await?;
+    let etherscan_config = env.config.get_etherscan_config(chain_id)?;
+    let mut abi =
+        loaders::etherscan::load_abi(etherscan_config.clone(), &address.to_string()).await?;
+    if merge_implementation {
+        let proxy = proxy::get_proxy(env, address)
+            .await?
+            .ok_or(anyhow!("{} is not a proxy", address))?;
+        let implementation_abi =
+            loaders::etherscan::load_abi(etherscan_config, &proxy.implementation.to_string())
+                .await?;
+        abi = proxy::merge_abis(implementation_abi, &abi);
+    }
+    let contract_info = env.add_contract(name, abi);
+    Ok(Value::Contract(contract_info, address, None))
+}
+
 fn fetch_abi<'a>(
     env: &'a mut Env,
     _receiver: &'a Value,
@@ -184,12 +214,10 @@ fn fetch_abi<'a>(
     async move {
         match args {
             [Value::Str(name), Value::Addr(address)] => {
-                let chain_id = env.get_chain_id().await?;
-                let etherscan_config = env.config.get_etherscan_config(chain_id)?;
-                let abi =
-                    loaders::etherscan::load_abi(etherscan_config, &address.to_string()).await?;
-                let contract_info = env.add_contract(name, abi);
-                Ok(Value::Contract(contract_info, *address))
+                fetch_contract_abi(env, name, *address, false).await
+            }
+            [Value::Str(name), Value::Addr(address), Value::Bool(merge_implementation)] => {
+                fetch_contract_abi(env, name, *address, *merge_implementation).await
             }
             _ => bail!("fetchAbi: invalid arguments"),
         }
@@ -295,7 +323,8 @@ fn abi_get_signature<'a>(
         }
 
         // Finally try to look it up from 4byte.directory
-        if let Ok(func) = loaders::four_bytes::find_function(selector).await {
+        let client = env.get_signatures_client();
+        if let Ok(func) = loaders::four_bytes::find_function(&client, selector).await {
             env.register_function(func.clone());
             Ok(Value::Str(func.signature()))
         } else {
@@ -335,10 +364,17 @@ lazy_static! {
     pub static ref ABI_FETCH: Arc<dyn FunctionDef> = AsyncMethod::arc(
         "fetch",
         fetch_abi,
-        vec![vec![
-            FunctionParam::new("name", Type::String),
-            FunctionParam::new("address", Type::Address)
-        ]]
+        vec![
+            vec![
+                FunctionParam::new("name", Type::String),
+                FunctionParam::new("address", Type::Address)
+            ],
+            vec![
+                FunctionParam::new("name", Type::String),
+                FunctionParam::new("address", Type::Address),
+                FunctionParam::new("mergeImplementation", Type::Bool)
+            ]
+        ]
     );
     pub static ref ABI_LOAD: Arc<dyn FunctionDef> = SyncMethod::arc(
         "load",
@@ -366,7 +402,7 @@ lazy_static! {
 mod tests {
     use std::str::FromStr;
 
-    use alloy::{hex, primitives::Address};
+    use alloy::{hex, primitives::Address, rpc::client::RpcClient, transports::mock::Asserter};
 
     use crate::interpreter::Config;
 
@@ -540,7 +576,9 @@ mod tests {
     async fn test_abi_get_signature() {
         let foundry_conf = foundry_config::load_config().unwrap();
         let config = Config::new(None, false, foundry_conf);
-        let mut env = Env::new(config);
+        let asserter = Asserter::new();
+        let mut env = Env::with_client(config, RpcClient::mocked(asserter.clone()));
+        asserter.push_success(&"transfer(address,uint256)");
 
         // Test with a known function selector (transfer(address,uint256))
         let transfer_selector = hex::decode("0xa9059cbb").unwrap();
@@ -1,13 +1,27 @@
-use std::{collections::HashMap, fs::DirEntry, sync::Arc};
+use std::{
+    collections::HashMap,
+    fs::DirEntry,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use crate::interpreter::{
+    env::WalletSigner,
     functions::{AsyncMethod, FunctionDef, FunctionParam, SyncMethod, SyncProperty},
+    remote_signer::RemoteSigner,
     types::{HashableIndexMap, ACCOUNT_TYPE},
     Env, Type, Value,
 };
 use alloy::{
-    primitives::Address,
-    signers::local::{LocalSigner, PrivateKeySigner},
+    dyn_abi::{Resolver, TypedData},
+    eips::eip7702::Authorization,
+    primitives::{Address, U256},
+    providers::Provider,
+    signers::{
+        ledger::HDPath,
+        local::{coins_bip39::English, LocalSigner, MnemonicBuilder, PrivateKeySigner},
+        Signer,
+    },
 };
 use anyhow::{anyhow, bail, Result};
 use futures::{future::BoxFuture, FutureExt};
@@ -42,13 +56,112 @@ fn load_private_key(env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<
         }
         _ => bail!("loadPrivateKey: invalid arguments"),
     };
-    env.set_signer(signer)?;
+    env.set_wallet_signer(signer)?;
+    if let (Some(alias), Some(address)) = (alias, env.get_default_sender()) {
+        env.set_account_alias(alias.as_str(), address);
+    }
+    Ok(get_default_sender(env))
+}
+
+/// Returns the derivation path of the account at `index` for the given scheme
+fn derivation_path(scheme: &str, index: usize) -> Result<String> {
+    match scheme {
+        "ledgerLive" => Ok(format!("m/44'/60'/{}'/0/0", index)),
+        "legacy" => Ok(format!("m/44'/60'/0'/{}", index)),
+        "bip44" => Ok(format!("m/44'/60'/0'/0/{}", index)),
+        _ => bail!(
+            "unknown derivation scheme {}, expected ledgerLive, legacy or bip44",
+            scheme
+        ),
+    }
+}
+
+/// Returns the derivation path given either as an index in the default scheme
+/// or as a full path such as `m/44'/60'/0'/0/1`
+fn get_derivation_path(value: &Value, default_scheme: &str) -> Result<String> {
+    let path = match value {
+        Value::Str(path) => path.clone(),
+        _ => return derivation_path(default_scheme, value.as_usize()?),
+    };
+    let is_valid = path.strip_prefix("m/").is_some_and(|components| {
+        components.split('/').all(|component| {
+            let index = component.strip_suffix('\'').unwrap_or(component);
+            index.parse::<u32>().is_ok()
+        })
+    });
+    if !is_valid {
+        bail!("invalid derivation path {}", path);
+    }
+    Ok(path)
+}
+
+fn mnemonic_signer(phrase: &str, path: &str) -> Result<PrivateKeySigner> {
+    Ok(MnemonicBuilder::<English>::default()
+        .phrase(phrase.trim())
+        .derivation_path(path)?
+        .build()?)
+}
+
+fn load_mnemonic(env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    let (phrase, path, alias) = match args {
+        [path] => (None, path, None),
+        [Value::Str(phrase), path] => (Some(phrase.clone()), path, None),
+        [Value::Str(phrase), path, Value::Str(alias)] => {
+            (Some(phrase.clone()), path, Some(alias.clone()))
+        }
+        [Value::Null, path, Value::Str(alias)] => (None, path, Some(alias.clone())),
+        _ => bail!("loadMnemonic: invalid arguments"),
+    };
+    let path = get_derivation_path(path, "bip44")?;
+    let phrase = match phrase {
+        Some(phrase) => phrase,
+        None => rpassword::prompt_password("Enter mnemonic: ")?,
+    };
+    env.set_wallet_signer(mnemonic_signer(&phrase, &path)?)?;
     if let (Some(alias), Some(address)) = (alias, env.get_default_sender()) {
         env.set_account_alias(alias.as_str(), address);
     }
     Ok(get_default_sender(env))
 }
 
+fn list_mnemonic_wallets(_env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    let (phrase, count, scheme) = match args {
+        [count] => (None, count.as_usize()?, "bip44"),
+        [Value::Str(phrase), count] => (Some(phrase.clone()), count.as_usize()?, "bip44"),
+        [Value::Str(phrase), count, Value::Str(scheme)] => {
+            (Some(phrase.clone()), count.as_usize()?, scheme.as_str())
+        }
+        [Value::Null, count, Value::Str(scheme)] => (None, count.as_usize()?, scheme.as_str()),
+        _ => bail!("listMnemonicWallets: invalid arguments"),
+    };
+    let phrase = match phrase {
+        Some(phrase) => phrase,
+        None => rpassword::prompt_password("Enter mnemonic: ")?,
+    };
+    let wallets = (0..count)
+        .map(|i| {
+            let signer = mnemonic_signer(&phrase, &derivation_path(scheme, i)?)?;
+            Ok(Value::Addr(signer.address()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Value::Array(wallets, Box::new(Type::Address)))
+}
+
+fn load_remote_signer(env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    let (url, address, alias) = match args {
+        [Value::Str(url), Value::Addr(address)] => (url, *address, None),
+        [Value::Str(url), Value::Addr(address), Value::Str(alias)] => {
+            (url, *address, Some(alias.clone()))
+        }
+        _ => bail!("loadRemoteSigner: invalid arguments"),
+    };
+    env.set_wallet_signer(RemoteSigner::new(url.parse()?, address))?;
+    if let Some(alias) = alias {
+        env.set_account_alias(alias.as_str(), address);
+    }
+    Ok(get_default_sender(env))
+}
+
 fn get_loaded_wallets(env: &Env, _receiver: &Value) -> Result<Value> {
     let loaded_wallets = env.get_loaded_wallets();
     let aliases = env.list_account_aliases();
@@ -81,6 +194,113 @@ fn select_wallet(env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Val
     Ok(get_default_sender(env))
 }
 
+fn keystores_dir() -> Result<PathBuf> {
+    let foundry_dir =
+        foundry_config::Config::foundry_dir().ok_or(anyhow!("foundry dir not found"))?;
+    Ok(foundry_dir.join("keystores"))
+}
+
+/// Returns the path of the keystore named `name` in `dir`,
+/// rejecting names that would point outside of the directory
+fn keystore_path(dir: &Path, name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        bail!("invalid keystore name {}", name);
+    }
+    Ok(dir.join(name))
+}
+
+/// Creates the keystores directory if needed,
+/// after checking that no keystore named `name` exists
+fn prepare_keystores_dir(dir: &Path, name: &str) -> Result<()> {
+    if keystore_path(dir, name)?.exists() {
+        bail!("keystore {} already exists", name);
+    }
+    std::fs::create_dir_all(dir)?;
+    Ok(())
+}
+
+/// Encrypts the key in a new keystore named `name` in `dir`
+fn write_keystore(
+    dir: &Path,
+    name: &str,
+    key: &PrivateKeySigner,
+    password: &str,
+) -> Result<Address> {
+    prepare_keystores_dir(dir, name)?;
+    let (signer, _) = LocalSigner::encrypt_keystore(
+        dir,
+        &mut rand::thread_rng(),
+        key.to_bytes(),
+        password,
+        Some(name),
+    )?;
+    Ok(signer.address())
+}
+
+/// Reads the address of a keystore without decrypting it
+fn read_keystore_address(dir: &Path, name: &str) -> Result<Address> {
+    let content = std::fs::read_to_string(keystore_path(dir, name)?)?;
+    let keystore: serde_json::Value = serde_json::from_str(&content)?;
+    match keystore["address"].as_str() {
+        Some(address) => Ok(address.parse()?),
+        None => bail!("keystore {} does not contain its address", name),
+    }
+}
+
+fn decrypt_keystore(dir: &Path, name: &str, password: &str) -> Result<PrivateKeySigner> {
+    Ok(LocalSigner::decrypt_keystore(
+        keystore_path(dir, name)?,
+        password,
+    )?)
+}
+
+fn prompt_new_password() -> Result<String> {
+    let password = rpassword::prompt_password("Enter password: ")?;
+    if rpassword::prompt_password("Confirm password: ")? != password {
+        bail!("passwords do not match");
+    }
+    Ok(password)
+}
+
+fn create_keystore(_env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    let name = match args {
+        [Value::Str(name)] => name,
+        _ => bail!("createKeystore: invalid arguments"),
+    };
+    let dir = keystores_dir()?;
+    prepare_keystores_dir(&dir, name)?;
+    let password = prompt_new_password()?;
+    let (signer, _) =
+        LocalSigner::new_keystore(dir, &mut rand::thread_rng(), password, Some(name.as_str()))?;
+    Ok(Value::Addr(signer.address()))
+}
+
+fn import_keystore(_env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    let (name, key): (&String, PrivateKeySigner) = match args {
+        [Value::Str(name)] => (
+            name,
+            rpassword::prompt_password("Enter private key: ")?.parse()?,
+        ),
+        [Value::Str(name), Value::Str(key)] => (name, key.parse()?),
+        [Value::Str(name), Value::FixBytes(bytes, 32)] => {
+            (name, PrivateKeySigner::from_bytes(bytes)?)
+        }
+        _ => bail!("importKeystore: invalid arguments"),
+    };
+    let dir = keystores_dir()?;
+    prepare_keystores_dir(&dir, name)?;
+    let password = prompt_new_password()?;
+    Ok(Value::Addr(write_keystore(&dir, name, &key, &password)?))
+}
+
+fn export_address(_env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    let name = match args {
+        [Value::Str(name)] => name,
+        _ => bail!("exportAddress: invalid arguments"),
+    };
+    Ok(Value::Addr(read_keystore_address(&keystores_dir()?, name)?))
+}
+
 fn load_keystore(env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
     let (account, alias, password) = match args {
         [Value::Str(account)] => (account.clone(), None, None),
@@ -98,11 +318,8 @@ fn load_keystore(env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Val
     } else {
         rpassword::prompt_password("Enter password: ")?
     };
-    let foundry_dir =
-        foundry_config::Config::foundry_dir().ok_or(anyhow!("foundry dir not found"))?;
-    let keystore_file_path = foundry_dir.join("keystores").join(account.as_str());
-    let signer = LocalSigner::decrypt_keystore(keystore_file_path, password)?;
-    env.set_signer(signer)?;
+    let signer = decrypt_keystore(&keystores_dir()?, &account, &password)?;
+    env.set_wallet_signer(signer)?;
     if let (Some(alias), Some(address)) = (alias, env.get_default_sender()) {
         env.set_account_alias(alias.as_str(), address);
     }
@@ -121,10 +338,7 @@ fn _get_filename(file: Result<DirEntry, std::io::Error>) -> Result<Value> {
     }
 }
 fn list_keystores(_env: &mut Env, _receiver: &Value, _args: &[Value]) -> Result<Value> {
-    let foundry_dir =
-        foundry_config::Config::foundry_dir().ok_or(anyhow!("foundry dir not found"))?;
-    let keystore_dir = foundry_dir.join("keystores");
-    let files = std::fs::read_dir(keystore_dir)?;
+    let files = std::fs::read_dir(keystores_dir()?)?;
     let valid_files: Vec<_> = files.filter_map(|file| _get_filename(file).ok()).collect();
     Ok(Value::Array(valid_files, Box::new(Type::String)))
 }
@@ -135,12 +349,16 @@ fn list_ledgers<'a>(
     args: &'a [Value],
 ) -> BoxFuture<'a, Result<Value>> {
     async move {
-        let count = match args {
-            [] => 5,
-            [value] => value.as_usize()?,
+        let (count, scheme) = match args {
+            [] => (5, "ledgerLive"),
+            [value] => (value.as_usize()?, "ledgerLive"),
+            [value, Value::Str(scheme)] => (value.as_usize()?, scheme.as_str()),
             _ => bail!("listLedgerWallets: invalid arguments"),
         };
-        let wallets = env.list_ledger_wallets(count).await?;
+        let paths = (0..count)
+            .map(|i| derivation_path(scheme, i).map(HDPath::Other))
+            .collect::<Result<Vec<_>>>()?;
+        let wallets = env.list_ledger_wallets(&paths).await?;
         Ok(Value::Array(
             wallets.into_iter().map(Value::Addr).collect(),
             Box::new(Type::Address),
@@ -165,13 +383,16 @@ fn load_ledger<'a>(
     args: &'a [Value],
 ) -> BoxFuture<'a, Result<Value>> {
     async move {
-        let (index, alias) = match args {
-            [] => (0, None),
-            [value] => (value.as_usize()?, None),
-            [value, Value::Str(alias)] => (value.as_usize()?, Some(alias.clone())),
+        let (path, alias) = match args {
+            [] => (derivation_path("ledgerLive", 0)?, None),
+            [value] => (get_derivation_path(value, "ledgerLive")?, None),
+            [value, Value::Str(alias)] => (
+                get_derivation_path(value, "ledgerLive")?,
+                Some(alias.clone()),
+            ),
             _ => bail!("loadLedger: invalid arguments"),
         };
-        env.load_ledger(index).await?;
+        env.load_ledger(HDPath::Other(path)).await?;
         if let (Some(alias), Some(address)) = (alias, env.get_default_sender()) {
             env.set_account_alias(alias.as_str(), address);
         }
@@ -180,6 +401,195 @@ fn load_ledger<'a>(
     .boxed()
 }
 
+fn message_bytes(value: &Value) -> Result<Vec<u8>> {
+    match value {
+        Value::Str(s) => Ok(s.as_bytes().to_vec()),
+        Value::Bytes(b) => Ok(b.clone()),
+        Value::FixBytes(b, size) => Ok(b[..*size].to_vec()),
+        _ => bail!(
+            "expected message to be a string or bytes, got {}",
+            value.get_type()
+        ),
+    }
+}
+
+fn sign_message<'a>(
+    env: &'a mut Env,
+    _receiver: &'a Value,
+    args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        let message = match args {
+            [message] => message_bytes(message)?,
+            _ => bail!("signMessage: invalid arguments"),
+        };
+        let signature = env.get_signer()?.sign_message(&message).await?;
+        Ok(Value::Bytes(signature.as_bytes().to_vec()))
+    }
+    .boxed()
+}
+
+/// Parses types given either as an `encodeType` string, e.g. `Mail(address to,string contents)`,
+/// or as a JSON object mapping type names to their fields
+fn parse_types(types: &Value) -> Result<Resolver> {
+    match types {
+        Value::Str(s) if s.trim_start().starts_with('{') => Ok(serde_json::from_str(s)?),
+        Value::Str(s) => {
+            let mut resolver = Resolver::default();
+            resolver.ingest_string(s)?;
+            Ok(resolver)
+        }
+        _ => Ok(serde_json::from_value(serde_json::to_value(types)?)?),
+    }
+}
+
+fn parse_typed_data(args: &[Value]) -> Result<TypedData> {
+    match args {
+        [Value::Str(json)] => Ok(serde_json::from_str(json)?),
+        [domain, types, Value::Str(primary_type), message] => Ok(TypedData {
+            domain: serde_json::from_value(serde_json::to_value(domain)?)?,
+            resolver: parse_types(types)?,
+            primary_type: primary_type.clone(),
+            message: serde_json::to_value(message)?,
+        }),
+        _ => bail!("signTypedData: invalid arguments"),
+    }
+}
+
+fn sign_typed_data<'a>(
+    env: &'a mut Env,
+    _receiver: &'a Value,
+    args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        let typed_data = parse_typed_data(args)?;
+        let signature = env
+            .get_signer()?
+            .sign_dynamic_typed_data(&typed_data)
+            .await?;
+        Ok(Value::Bytes(signature.as_bytes().to_vec()))
+    }
+    .boxed()
+}
+
+/// Signs an EIP-7702 authorization for the current account.
+/// The transaction including it is expected to be sent by the same account,
+/// which increments its nonce before the authorization is processed, so the default
+/// nonce is the pending nonce plus one, unless the `sponsored` option is set
+#[derive(Debug)]
+struct SignAuthorization;
+
+impl FunctionDef for SignAuthorization {
+    fn name(&self) -> String {
+        "signAuthorization".to_string()
+    }
+
+    fn get_valid_args(&self, _receiver: &Option<Value>) -> Vec<Vec<FunctionParam>> {
+        vec![
+            vec![FunctionParam::new("delegate", Type::Address)],
+            vec![
+                FunctionParam::new("delegate", Type::Address),
+                FunctionParam::new("nonce", Type::Uint(256)),
+            ],
+            vec![
+                FunctionParam::new("delegate", Type::Address),
+                FunctionParam::new("nonce", Type::Uint(256)),
+                FunctionParam::new("chainId", Type::Uint(256)),
+            ],
+            vec![
+                FunctionParam::new("delegate", Type::Address),
+                FunctionParam::new("nonce", Type::Null),
+                FunctionParam::new("chainId", Type::Uint(256)),
+            ],
+        ]
+    }
+
+    fn is_property(&self) -> bool {
+        false
+    }
+
+    fn execute<'a>(
+        &'a self,
+        env: &'a mut Env,
+        values: &'a [Value],
+        options: &'a HashableIndexMap<String, Value>,
+    ) -> BoxFuture<'a, Result<Value>> {
+        async move {
+            let mut sponsored = false;
+            for (key, value) in options.0.iter() {
+                match key.as_str() {
+                    "sponsored" => sponsored = value.as_bool()?,
+                    _ => bail!("unexpected key {}", key),
+                }
+            }
+            let (delegate, nonce, chain_id) = match &values[1..] {
+                [Value::Addr(delegate)] => (*delegate, None, None),
+                [Value::Addr(delegate), nonce] => (*delegate, Some(nonce.as_u64()?), None),
+                [Value::Addr(delegate), Value::Null, chain_id] => {
+                    (*delegate, None, Some(chain_id.as_u256()?))
+                }
+                [Value::Addr(delegate), nonce, chain_id] => {
+                    (*delegate, Some(nonce.as_u64()?), Some(chain_id.as_u256()?))
+                }
+                _ => bail!("signAuthorization: invalid arguments"),
+            };
+            let signer = env.get_signer()?;
+            let nonce = match nonce {
+                Some(nonce) => nonce,
+                None => {
+                    let nonce = env
+                        .get_provider()
+                        .get_transaction_count(signer.address())
+                        .pending()
+                        .await?;
+                    if sponsored {
+                        nonce
+                    } else {
+                        nonce + 1
+                    }
+                }
+            };
+            let chain_id = match chain_id {
+                Some(chain_id) => chain_id,
+                None => U256::from(env.get_chain_id().await?),
+            };
+            let authorization = Authorization {
+                chain_id,
+                address: delegate,
+                nonce,
+            };
+            let signature = signer.sign_authorization(&authorization).await?;
+            Ok(Value::from(authorization.into_signed(signature)))
+        }
+        .boxed()
+    }
+}
+
+/// Lists the transactions sent from the REPL that are not mined yet,
+/// forgetting the ones that were mined or replaced
+fn pending_transactions<'a>(
+    env: &'a mut Env,
+    _receiver: &'a Value,
+    _args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        let mut pending = vec![];
+        let mut done = vec![];
+        for (hash, chain) in env.get_sent_transactions() {
+            let provider = env.get_connected_chain_provider(chain.as_deref())?;
+            match provider.get_transaction_by_hash(hash).await? {
+                Some(tx) if tx.block_number.is_none() => {
+                    pending.push(Value::Transaction(hash, chain))
+                }
+                _ => done.push(hash),
+            }
+        }
+        env.untrack_transactions(&done);
+        Ok(Value::Array(pending, Box::new(Type::Transaction)))
+    }
+    .boxed()
+}
+
 lazy_static! {
     pub static ref ACCOUNT_CURRENT: Arc<dyn FunctionDef> =
         SyncProperty::arc("current", get_account);
@@ -227,10 +637,57 @@ lazy_static! {
             ],
         ]
     );
+    pub static ref ACCOUNT_CREATE_KEYSTORE: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "createKeystore",
+        create_keystore,
+        vec![vec![FunctionParam::new("name", Type::String)]]
+    );
+    pub static ref ACCOUNT_IMPORT_KEYSTORE: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "importKeystore",
+        import_keystore,
+        vec![
+            vec![FunctionParam::new("name", Type::String)],
+            vec![
+                FunctionParam::new("name", Type::String),
+                FunctionParam::new("privateKey", Type::String)
+            ],
+            vec![
+                FunctionParam::new("name", Type::String),
+                FunctionParam::new("privateKey", Type::FixBytes(32))
+            ],
+        ]
+    );
+    pub static ref ACCOUNT_EXPORT_ADDRESS: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "exportAddress",
+        export_address,
+        vec![vec![FunctionParam::new("name", Type::String)]]
+    );
+    pub static ref ACCOUNT_LOAD_REMOTE_SIGNER: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "loadRemoteSigner",
+        load_remote_signer,
+        vec![
+            vec![
+                FunctionParam::new("url", Type::String),
+                FunctionParam::new("address", Type::Address)
+            ],
+            vec![
+                FunctionParam::new("url", Type::String),
+                FunctionParam::new("address", Type::Address),
+                FunctionParam::new("alias", Type::String)
+            ],
+        ]
+    );
     pub static ref ACCOUNT_LIST_LEDGER_WALLETS: Arc<dyn FunctionDef> = AsyncMethod::arc(
         "listLedgerWallets",
         list_ledgers,
-        vec![vec![], vec![FunctionParam::new("count", Type::Uint(256))]]
+        vec![
+            vec![],
+            vec![FunctionParam::new("count", Type::Uint(256))],
+            vec![
+                FunctionParam::new("count", Type::Uint(256)),
+                FunctionParam::new("scheme", Type::String)
+            ]
+        ]
     );
     pub static ref ACCOUNT_LOAD_LEDGER: Arc<dyn FunctionDef> = AsyncMethod::arc(
         "loadLedger",
@@ -238,12 +695,74 @@ lazy_static! {
         vec![
             vec![],
             vec![FunctionParam::new("index", Type::Uint(256))],
+            vec![FunctionParam::new("path", Type::String)],
             vec![
                 FunctionParam::new("index", Type::Uint(256)),
                 FunctionParam::new("alias", Type::String)
+            ],
+            vec![
+                FunctionParam::new("path", Type::String),
+                FunctionParam::new("alias", Type::String)
             ]
         ]
     );
+    pub static ref ACCOUNT_LOAD_MNEMONIC: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "loadMnemonic",
+        load_mnemonic,
+        vec![
+            vec![FunctionParam::new("index", Type::Uint(256))],
+            vec![FunctionParam::new("path", Type::String)],
+            vec![
+                FunctionParam::new("mnemonic", Type::String),
+                FunctionParam::new("index", Type::Uint(256))
+            ],
+            vec![
+                FunctionParam::new("mnemonic", Type::String),
+                FunctionParam::new("path", Type::String)
+            ],
+            vec![
+                FunctionParam::new("mnemonic", Type::String),
+                FunctionParam::new("index", Type::Uint(256)),
+                FunctionParam::new("alias", Type::String)
+            ],
+            vec![
+                FunctionParam::new("mnemonic", Type::String),
+                FunctionParam::new("path", Type::String),
+                FunctionParam::new("alias", Type::String)
+            ],
+            vec![
+                FunctionParam::new("mnemonic", Type::Null),
+                FunctionParam::new("index", Type::Uint(256)),
+                FunctionParam::new("alias", Type::String)
+            ],
+            vec![
+                FunctionParam::new("mnemonic", Type::Null),
+                FunctionParam::new("path", Type::String),
+                FunctionParam::new("alias", Type::String)
+            ],
+        ]
+    );
+    pub static ref ACCOUNT_LIST_MNEMONIC_WALLETS: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "listMnemonicWallets",
+        list_mnemonic_wallets,
+        vec![
+            vec![FunctionParam::new("count", Type::Uint(256))],
+            vec![
+                FunctionParam::new("mnemonic", Type::String),
+                FunctionParam::new("count", Type::Uint(256))
+            ],
+            vec![
+                FunctionParam::new("mnemonic", Type::String),
+                FunctionParam::new("count", Type::Uint(256)),
+                FunctionParam::new("scheme", Type::String)
+            ],
+            vec![
+                FunctionParam::new("mnemonic", Type::Null),
+                FunctionParam::new("count", Type::Uint(256)),
+                FunctionParam::new("scheme", Type::String)
+            ],
+        ]
+    );
     pub static ref ACCOUNT_GET_LOADED: Arc<dyn FunctionDef> =
         SyncProperty::arc("loaded", get_loaded_wallets);
     pub static ref ACCOUNT_SELECT: Arc<dyn FunctionDef> = SyncMethod::arc(
@@ -262,4 +781,55 @@ lazy_static! {
             FunctionParam::new("alias", Type::String)
         ],]
     );
+    pub static ref ACCOUNT_SIGN_MESSAGE: Arc<dyn FunctionDef> = AsyncMethod::arc(
+        "signMessage",
+        sign_message,
+        vec![vec![FunctionParam::new("message", Type::Any)]]
+    );
+    pub static ref ACCOUNT_SIGN_TYPED_DATA: Arc<dyn FunctionDef> = AsyncMethod::arc(
+        "signTypedData",
+        sign_typed_data,
+        vec![
+            vec![FunctionParam::new("typedData", Type::String)],
+            vec![
+                FunctionParam::new("domain", Type::Any),
+                FunctionParam::new("types", Type::Any),
+                FunctionParam::new("primaryType", Type::String),
+                FunctionParam::new("message", Type::Any)
+            ]
+        ]
+    );
+    pub static ref ACCOUNT_SIGN_AUTHORIZATION: Arc<dyn FunctionDef> = Arc::new(SignAuthorization);
+    pub static ref ACCOUNT_PENDING: Arc<dyn FunctionDef> =
+        AsyncMethod::arc("pending", pending_transactions, vec![vec![]]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let dir = std::env::temp_dir().join(format!("eclair-keystores-{}", std::process::id()));
+        let key: PrivateKeySigner =
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+                .parse()
+                .unwrap();
+
+        let address = write_keystore(&dir, "test", &key, "password").unwrap();
+        assert_eq!(address, key.address());
+        assert!(write_keystore(&dir, "test", &key, "password").is_err());
+        assert_eq!(read_keystore_address(&dir, "test").unwrap(), address);
+        assert_eq!(
+            decrypt_keystore(&dir, "test", "password")
+                .unwrap()
+                .address(),
+            address
+        );
+        assert!(decrypt_keystore(&dir, "test", "wrong").is_err());
+        assert!(read_keystore_address(&dir, "../test").is_err());
+        assert!(write_keystore(&dir, "..", &key, "password").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -4,12 +4,13 @@ use alloy::{
     network::TransactionBuilder, providers::Provider, rpc::types::TransactionRequest,
     transports::BoxFuture,
 };
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use futures::FutureExt;
 use lazy_static::lazy_static;
 
 use crate::interpreter::{
-    functions::{AsyncMethod, AsyncProperty, FunctionDef, FunctionParam},
+    functions::{AsyncProperty, CallOptions, FunctionDef, FunctionParam},
+    types::HashableIndexMap,
     Env, Type, Value,
 };
 
@@ -26,31 +27,58 @@ fn get_balance<'a>(env: &'a Env, receiver: &'a Value) -> BoxFuture<'a, Result<Va
     .boxed()
 }
 
-fn transfer<'a>(
-    env: &'a mut Env,
-    receiver: &'a Value,
-    args: &'a [Value],
-) -> BoxFuture<'a, Result<Value>> {
-    async move {
-        let provider = env.get_provider();
-        let value = args
-            .first()
-            .ok_or(anyhow!("Missing value"))
-            .and_then(|v| v.as_u256())?;
-        let addr = receiver.as_address()?;
-        let tx_req = TransactionRequest::default().with_to(addr).value(value);
-        let tx = provider.send_transaction(tx_req).await?;
-        Ok(Value::Transaction(*tx.tx_hash()))
+#[derive(Debug)]
+struct Transfer;
+
+impl FunctionDef for Transfer {
+    fn name(&self) -> String {
+        "transfer".to_string()
+    }
+
+    fn get_valid_args(&self, _receiver: &Option<Value>) -> Vec<Vec<FunctionParam>> {
+        vec![vec![FunctionParam::new("amount", Type::Uint(256))]]
+    }
+
+    fn is_property(&self) -> bool {
+        false
+    }
+
+    fn execute<'a>(
+        &'a self,
+        env: &'a mut Env,
+        values: &'a [Value],
+        options: &'a HashableIndexMap<String, Value>,
+    ) -> BoxFuture<'a, Result<Value>> {
+        async move {
+            let opts: CallOptions = options.try_into()?;
+            opts.validate_send()?;
+            if opts.value().is_some() {
+                bail!("transfer amount should be passed as an argument");
+            }
+            let provider = env.get_provider();
+            let value = values
+                .get(1)
+                .ok_or(anyhow!("Missing value"))
+                .and_then(|v| v.as_u256())?;
+            let addr = values[0].as_address()?;
+            let from = env
+                .get_default_sender()
+                .ok_or(anyhow!("no wallet connected"))?;
+            let tx_req = TransactionRequest::default()
+                .with_from(from)
+                .with_to(addr)
+                .value(value);
+            let tx_req = opts.apply_to_send(env, &provider, tx_req).await?;
+            let tx = provider.send_transaction(tx_req).await?;
+            env.track_transaction(*tx.tx_hash(), None);
+            Ok(Value::Transaction(*tx.tx_hash(), None))
+        }
+        .boxed()
     }
-    .boxed()
 }
 
 lazy_static! {
     pub static ref ADDRESS_BALANCE: Arc<dyn FunctionDef> =
         AsyncProperty::arc("balance", get_balance);
-    pub static ref ADDRESS_TRANSFER: Arc<dyn FunctionDef> = AsyncMethod::arc(
-        "transfer",
-        transfer,
-        vec![vec![FunctionParam::new("amount", Type::Uint(256))]]
-    );
+    pub static ref ADDRESS_TRANSFER: Arc<dyn FunctionDef> = Arc::new(Transfer);
 }
@@ -1,13 +1,21 @@
 use std::sync::Arc;
 
-use alloy::{eips::BlockId, providers::Provider};
-use anyhow::{anyhow, Ok, Result};
-use futures::{future::BoxFuture, FutureExt};
+use alloy::{
+    eips::{BlockId, BlockNumberOrTag},
+    primitives::U256,
+    providers::Provider,
+    rpc::types::Block,
+};
+use anyhow::{anyhow, bail, Ok, Result};
+use futures::{future::BoxFuture, FutureExt, StreamExt};
+use indexmap::IndexMap;
 use lazy_static::lazy_static;
 
 use crate::interpreter::{
-    functions::{AsyncProperty, FunctionDef},
-    Env, Value,
+    fees::FeeStrategy,
+    functions::{AsyncMethod, AsyncProperty, Function, FunctionDef, FunctionParam},
+    types::HashableIndexMap,
+    utils, Env, Type, Value,
 };
 
 fn get_chain_id<'a>(env: &'a Env, _arg: &'a Value) -> BoxFuture<'a, Result<Value>> {
@@ -34,6 +42,139 @@ fn get_timestamp<'a>(env: &'a Env, _arg: &'a Value) -> BoxFuture<'a, Result<Valu
     .boxed()
 }
 
+fn block_to_value(block: Block) -> Value {
+    let header = block.header;
+    let mut fields = IndexMap::new();
+    fields.insert("number".to_string(), header.number.into());
+    fields.insert("hash".to_string(), Value::FixBytes(header.hash, 32));
+    fields.insert("timestamp".to_string(), header.timestamp.into());
+    if let Some(base_fee) = header.base_fee_per_gas {
+        fields.insert("basefee".to_string(), base_fee.into());
+    }
+    fields.insert("gasUsed".to_string(), header.gas_used.into());
+    fields.insert("gasLimit".to_string(), header.gas_limit.into());
+    fields.insert(
+        "transactionsCount".to_string(),
+        Value::Uint(U256::from(block.transactions.len()), 256),
+    );
+    Value::NamedTuple("Block".to_string(), HashableIndexMap(fields))
+}
+
+async fn call_with_block(env: &mut Env, func: &Function, number: u64) -> Result<()> {
+    let block = env
+        .get_provider()
+        .get_block(BlockId::number(number))
+        .await?
+        .ok_or(anyhow!("block {} not found", number))?;
+    func.execute(env, &[block_to_value(block)]).await?;
+    Ok(())
+}
+
+/// Calls `func` with each new block, subscribing to new heads
+/// when the transport supports it and polling otherwise
+async fn watch_new_blocks(env: &mut Env, func: &Function) -> Result<()> {
+    let provider = env.get_provider();
+    if provider.client().pubsub_frontend().is_some() {
+        let mut headers = provider.subscribe_blocks().await?.into_stream();
+        while let Some(header) = headers.next().await {
+            call_with_block(env, func, header.number).await?;
+        }
+        return Ok(());
+    }
+
+    let interval = provider.client().poll_interval();
+    let mut next_block = provider.get_block_number().await? + 1;
+    loop {
+        let latest = provider.get_block_number().await?;
+        for number in next_block..=latest {
+            call_with_block(env, func, number).await?;
+        }
+        next_block = next_block.max(latest + 1);
+        tokio::time::sleep(interval).await;
+    }
+}
+
+fn watch_blocks<'a>(
+    env: &'a mut Env,
+    _receiver: &'a Value,
+    args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        let func = match args.first() {
+            Some(Value::Func(func)) => func,
+            _ => bail!("block.watch expects a function as an argument"),
+        };
+        eprintln!("watching blocks, press Ctrl-C to stop");
+        let depth = env.scope_depth();
+        match utils::run_until_interrupted(watch_new_blocks(env, func)).await {
+            Some(result) => result?,
+            None => env.truncate_scopes(depth),
+        }
+        Ok(Value::Null)
+    }
+    .boxed()
+}
+
+/// Reward percentiles returned by default, matching the slow, normal and fast fee strategies
+const DEFAULT_REWARD_PERCENTILES: [f64; 3] = [
+    FeeStrategy::Slow.reward_percentile(),
+    FeeStrategy::Normal.reward_percentile(),
+    FeeStrategy::Fast.reward_percentile(),
+];
+
+fn fee_history<'a>(
+    env: &'a mut Env,
+    _receiver: &'a Value,
+    args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        let (blocks, percentiles) = match args {
+            [blocks] => (blocks.as_u64()?, DEFAULT_REWARD_PERCENTILES.to_vec()),
+            [blocks, Value::Array(percentiles, _)] => (
+                blocks.as_u64()?,
+                percentiles
+                    .iter()
+                    .map(Value::as_f64)
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            _ => bail!("feeHistory function expects a number of blocks and optional percentiles"),
+        };
+        let last_block = match env.block() {
+            BlockId::Number(tag) => tag,
+            BlockId::Hash(_) => BlockNumberOrTag::Latest,
+        };
+        let history = env
+            .get_provider()
+            .get_fee_history(blocks, last_block, &percentiles)
+            .await?;
+
+        let uint_array = |values: Vec<u128>| {
+            Value::Array(
+                values.into_iter().map(Value::from).collect(),
+                Box::new(Type::Uint(256)),
+            )
+        };
+        let rewards = history
+            .reward
+            .unwrap_or_default()
+            .into_iter()
+            .map(uint_array)
+            .collect();
+        let mut fields = IndexMap::new();
+        fields.insert("oldestBlock".to_string(), history.oldest_block.into());
+        fields.insert("baseFees".to_string(), uint_array(history.base_fee_per_gas));
+        fields.insert(
+            "rewards".to_string(),
+            Value::Array(rewards, Box::new(Type::Array(Box::new(Type::Uint(256))))),
+        );
+        Ok(Value::NamedTuple(
+            "FeeHistory".to_string(),
+            HashableIndexMap(fields),
+        ))
+    }
+    .boxed()
+}
+
 lazy_static! {
     pub static ref BLOCK_CHAIN_ID: Arc<dyn FunctionDef> =
         AsyncProperty::arc("chainid", get_chain_id);
@@ -43,4 +184,20 @@ lazy_static! {
         AsyncProperty::arc("number", get_block_number);
     pub static ref BLOCK_TIMESTAMP: Arc<dyn FunctionDef> =
         AsyncProperty::arc("timestamp", get_timestamp);
+    pub static ref BLOCK_WATCH: Arc<dyn FunctionDef> = AsyncMethod::arc(
+        "watch",
+        watch_blocks,
+        vec![vec![FunctionParam::new("f", Type::Function)]]
+    );
+    pub static ref BLOCK_FEE_HISTORY: Arc<dyn FunctionDef> = AsyncMethod::arc(
+        "feeHistory",
+        fee_history,
+        vec![
+            vec![FunctionParam::new("blocks", Type::Uint(256))],
+            vec![
+                FunctionParam::new("blocks", Type::Uint(256)),
+                FunctionParam::new("percentiles", Type::Array(Box::new(Type::Decimal)))
+            ]
+        ]
+    );
 }
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use futures::{future::BoxFuture, FutureExt};
+use lazy_static::lazy_static;
+
+use crate::interpreter::{
+    functions::{AsyncMethod, FunctionDef, FunctionParam},
+    Env, Type, Value,
+};
+
+fn on<'a>(
+    env: &'a mut Env,
+    receiver: &'a Value,
+    args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        let chain = match args {
+            [Value::Str(chain)] => chain.clone(),
+            _ => bail!("on function expects an RPC URL or endpoint alias as argument"),
+        };
+        // connect eagerly so that invalid chains are reported right away
+        env.get_chain_provider(Some(&chain)).await?;
+        match receiver {
+            Value::Contract(info, addr, _) => Ok(Value::Contract(info.clone(), *addr, Some(chain))),
+            Value::Transaction(tx, _) => Ok(Value::Transaction(*tx, Some(chain))),
+            _ => bail!("on function expects a contract or a transaction as receiver"),
+        }
+    }
+    .boxed()
+}
+
+lazy_static! {
+    pub static ref CHAIN_ON: Arc<dyn FunctionDef> = AsyncMethod::arc(
+        "on",
+        on,
+        vec![vec![FunctionParam::new("chain", Type::String)]]
+    );
+}
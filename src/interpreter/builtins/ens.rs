@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use futures::{future::BoxFuture, FutureExt};
+use lazy_static::lazy_static;
+
+use crate::interpreter::{
+    ens,
+    functions::{AsyncMethod, FunctionDef, FunctionParam, SyncMethod},
+    Env, Type, Value,
+};
+
+fn resolve<'a>(
+    env: &'a mut Env,
+    _receiver: &'a Value,
+    args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        match args {
+            [Value::Str(name)] => Ok(Value::Addr(ens::resolve(env, name).await?)),
+            _ => bail!("resolve function expects an ENS name as argument"),
+        }
+    }
+    .boxed()
+}
+
+fn lookup<'a>(
+    env: &'a mut Env,
+    _receiver: &'a Value,
+    args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        match args {
+            [Value::Addr(addr)] => Ok(ens::lookup(env, *addr)
+                .await?
+                .map_or(Value::Null, Value::Str)),
+            _ => bail!("lookup function expects an address as argument"),
+        }
+    }
+    .boxed()
+}
+
+fn namehash(_env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    match args {
+        [Value::Str(name)] => Ok(Value::FixBytes(ens::namehash(name)?, 32)),
+        _ => bail!("namehash function expects an ENS name as argument"),
+    }
+}
+
+lazy_static! {
+    pub static ref ENS_RESOLVE: Arc<dyn FunctionDef> = AsyncMethod::arc(
+        "resolve",
+        resolve,
+        vec![vec![FunctionParam::new("name", Type::String)]]
+    );
+    pub static ref ENS_LOOKUP: Arc<dyn FunctionDef> = AsyncMethod::arc(
+        "lookup",
+        lookup,
+        vec![vec![FunctionParam::new("address", Type::Address)]]
+    );
+    pub static ref ENS_NAMEHASH: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "namehash",
+        namehash,
+        vec![vec![FunctionParam::new("name", Type::String)]]
+    );
+}
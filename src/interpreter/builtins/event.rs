@@ -1,12 +1,21 @@
 use std::sync::Arc;
 
 use crate::interpreter::{
-    functions::{FunctionDef, SyncProperty},
-    Env, Type, Value,
+    functions::{FunctionDef, FunctionParam, SyncProperty},
+    types::{HashableIndexMap, LOG_TYPE},
+    utils, Env, Type, Value,
 };
-use anyhow::{bail, Result};
+use alloy::{
+    dyn_abi::{DynSolValue, Specifier},
+    json_abi::{Event, EventParam},
+    primitives::{keccak256, B256},
+};
+use anyhow::{anyhow, bail, Result};
+use futures::{future::BoxFuture, FutureExt};
 use lazy_static::lazy_static;
 
+use super::events::{address_params, build_filter, get_logs, EventOptions};
+
 pub fn event_selector(_env: &Env, receiver: &Value) -> Result<Value> {
     let event_abi = match receiver {
         Value::TypeObject(Type::Event(event)) => event,
@@ -16,7 +25,182 @@ pub fn event_selector(_env: &Env, receiver: &Value) -> Result<Value> {
     Ok(event_abi.selector().into())
 }
 
+fn cast_arg(param: &EventParam, value: &Value) -> Result<Value> {
+    let type_: Type = param.resolve()?.into();
+    type_.cast(value)
+}
+
+/// Appends the in-place encoding of a value to the preimage of a topic,
+/// padding every value to 32 bytes and omitting lengths and offsets
+fn encode_topic_preimage(value: &DynSolValue, out: &mut Vec<u8>) {
+    if let Some(word) = value.as_word() {
+        out.extend_from_slice(word.as_slice());
+    } else if let Some(bytes) = value.as_packed_seq() {
+        out.extend_from_slice(bytes);
+        out.resize(out.len().next_multiple_of(32), 0);
+    } else if let Some(values) = value.as_fixed_seq().or(value.as_array()) {
+        for value in values {
+            encode_topic_preimage(value, out);
+        }
+    }
+}
+
+/// Encodes an indexed argument as a topic, hashing types that
+/// do not fit in a single word
+fn encode_topic(param: &EventParam, value: &Value) -> Result<B256> {
+    let value = DynSolValue::try_from(&cast_arg(param, value)?)?;
+    if let Some(word) = value.as_word() {
+        return Ok(word);
+    }
+    if let Some(bytes) = value.as_packed_seq() {
+        return Ok(keccak256(bytes));
+    }
+    let mut preimage = vec![];
+    encode_topic_preimage(&value, &mut preimage);
+    Ok(keccak256(preimage))
+}
+
+fn matches_filters(decoded: &Value, filters: &[(String, Value)]) -> bool {
+    filters
+        .iter()
+        .all(|(name, expected)| decoded.get_field(name).is_ok_and(|v| &v == expected))
+}
+
+async fn fetch_event(
+    env: &mut Env,
+    event: &Event,
+    args: &[Value],
+    mut options: EventOptions,
+) -> Result<Value> {
+    // anonymous events have no selector, so their indexed arguments start at topic0
+    if !event.anonymous {
+        options.topic0 = Some(event.selector());
+    }
+    let mut body_filters = vec![];
+    for (name, value) in options.args.iter() {
+        let param = event
+            .inputs
+            .iter()
+            .find(|p| &p.name == name)
+            .ok_or(anyhow!("event {} has no argument {}", event.name, name))?;
+        if !param.indexed {
+            body_filters.push((name.clone(), cast_arg(param, value)?));
+            continue;
+        }
+        let position = event
+            .inputs
+            .iter()
+            .filter(|p| p.indexed)
+            .position(|p| p == param)
+            .map(|position| position + usize::from(!event.anonymous));
+        let topic = Some(encode_topic(param, value)?);
+        match position {
+            Some(0) => options.topic0 = topic,
+            Some(1) => options.topic1 = topic,
+            Some(2) => options.topic2 = topic,
+            Some(3) => options.topic3 = topic,
+            _ => bail!("invalid indexed argument {}", name),
+        }
+    }
+
+    let filter = build_filter(args, &options)?;
+    let logs = get_logs(env, &filter, options.chunk_size).await?;
+    let mut parsed_logs = vec![];
+    for log in logs {
+        // logs from other events with the same selector (e.g. ERC721 and ERC20 transfers)
+        // have a different number of topics and cannot be decoded
+        let Ok(decoded) = utils::decode_log_args(&log, event) else {
+            continue;
+        };
+        if matches_filters(&decoded, &body_filters) {
+            parsed_logs.push(utils::log_to_value_with_event(log, Some(event))?);
+        }
+    }
+    Ok(Value::Array(parsed_logs, Box::new(LOG_TYPE.clone())))
+}
+
+#[derive(Debug)]
+struct EventFetch;
+
+impl FunctionDef for EventFetch {
+    fn name(&self) -> String {
+        "fetch".to_string()
+    }
+
+    fn get_valid_args(&self, _receiver: &Option<Value>) -> Vec<Vec<FunctionParam>> {
+        address_params()
+    }
+
+    fn is_property(&self) -> bool {
+        false
+    }
+
+    fn execute<'a>(
+        &'a self,
+        env: &'a mut Env,
+        values: &'a [Value],
+        options: &'a HashableIndexMap<String, Value>,
+    ) -> BoxFuture<'a, Result<Value>> {
+        async move {
+            let event = match values.first() {
+                Some(Value::TypeObject(Type::Event(event))) => event.clone(),
+                _ => bail!("fetch function expects receiver to be an event"),
+            };
+            let parsed_opts = options.try_into()?;
+            fetch_event(env, &event, &values[1..], parsed_opts).await
+        }
+        .boxed()
+    }
+}
+
 lazy_static! {
     pub static ref EVENT_SELECTOR: Arc<dyn FunctionDef> =
         SyncProperty::arc("selector", event_selector);
+    pub static ref EVENT_FETCH: Arc<dyn FunctionDef> = Arc::new(EventFetch);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{address, U256},
+        sol_types::{sol_data, EventTopic},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_encode_topic() {
+        let event =
+            Event::parse("event Named(address indexed owner, string indexed name)").unwrap();
+        let owner = address!("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+        assert_eq!(
+            encode_topic(&event.inputs[0], &Value::Addr(owner)).unwrap(),
+            B256::from(owner.into_word())
+        );
+        assert_eq!(
+            encode_topic(&event.inputs[1], &Value::Str("vitalik".to_string())).unwrap(),
+            keccak256("vitalik")
+        );
+
+        let event =
+            Event::parse("event Complex(uint16[] indexed ids, (uint256,string) indexed data)")
+                .unwrap();
+        let ids = Value::Array(
+            vec![Value::from(1u64), Value::from(2u64)],
+            Box::new(Type::Uint(16)),
+        );
+        assert_eq!(
+            encode_topic(&event.inputs[0], &ids).unwrap(),
+            <sol_data::Array<sol_data::Uint<16>> as EventTopic>::encode_topic(&vec![1u16, 2]).0
+        );
+        let data = Value::Tuple(vec![Value::from(1u64), Value::Str("vitalik".to_string())]);
+        assert_eq!(
+            encode_topic(&event.inputs[1], &data).unwrap(),
+            <(sol_data::Uint<256>, sol_data::String) as EventTopic>::encode_topic(&(
+                U256::from(1),
+                "vitalik".to_string()
+            ))
+            .0
+        );
+    }
 }
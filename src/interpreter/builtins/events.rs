@@ -1,32 +1,83 @@
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
 
-use alloy::{primitives::B256, providers::Provider, rpc::types::Filter};
-use anyhow::{bail, Result};
-use futures::{future::BoxFuture, FutureExt};
+use alloy::{
+    primitives::B256,
+    providers::Provider,
+    rpc::types::{Filter, Log},
+};
+use anyhow::{anyhow, bail, Result};
+use futures::{future::BoxFuture, stream, FutureExt, StreamExt};
+use indexmap::IndexMap;
 use lazy_static::lazy_static;
 
-use crate::interpreter::{functions::FunctionDef, types::LOG_TYPE, utils, Env, Type, Value};
+use crate::interpreter::{
+    functions::{Function, FunctionDef, FunctionParam},
+    types::{HashableIndexMap, LOG_TYPE},
+    utils, Env, Type, Value,
+};
+
+const EVENT_OPTIONS: [&str; 7] = [
+    "topic0",
+    "topic1",
+    "topic2",
+    "topic3",
+    "fromBlock",
+    "toBlock",
+    "chunkSize",
+];
+
+const DEFAULT_CHUNK_SIZE: u64 = 10_000;
+const MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// Errors returned by RPC providers when too many logs or blocks are queried at once
+const RANGE_ERROR_PATTERNS: [&str; 9] = [
+    "returned more than",
+    "too many results",
+    "too many logs",
+    "block range",
+    "range is too",
+    "range too",
+    "response size",
+    "is limited to",
+    "exceeds max results",
+];
 
 #[derive(Debug)]
-struct EventOptions {
-    topic0: Option<B256>,
-    topic1: Option<B256>,
-    topic2: Option<B256>,
-    topic3: Option<B256>,
-    from_block: Option<u64>,
-    to_block: Option<u64>,
+pub(super) struct EventOptions {
+    pub topic0: Option<B256>,
+    pub topic1: Option<B256>,
+    pub topic2: Option<B256>,
+    pub topic3: Option<B256>,
+    pub from_block: Option<u64>,
+    pub to_block: Option<u64>,
+    pub chunk_size: Option<u64>,
+    /// Options that are not part of the filter, used to match event arguments
+    pub args: IndexMap<String, Value>,
 }
 
-impl TryFrom<&crate::interpreter::types::HashableIndexMap<String, Value>> for EventOptions {
+impl TryFrom<&HashableIndexMap<String, Value>> for EventOptions {
     type Error = anyhow::Error;
 
-    fn try_from(map: &crate::interpreter::types::HashableIndexMap<String, Value>) -> Result<Self> {
+    fn try_from(map: &HashableIndexMap<String, Value>) -> Result<Self> {
         let topic0 = map.0.get("topic0").map(|v| v.as_b256()).transpose()?;
         let topic1 = map.0.get("topic1").map(|v| v.as_b256()).transpose()?;
         let topic2 = map.0.get("topic2").map(|v| v.as_b256()).transpose()?;
         let topic3 = map.0.get("topic3").map(|v| v.as_b256()).transpose()?;
         let from_block = map.0.get("fromBlock").map(|v| v.as_u64()).transpose()?;
         let to_block = map.0.get("toBlock").map(|v| v.as_u64()).transpose()?;
+        let chunk_size = map.0.get("chunkSize").map(|v| v.as_u64()).transpose()?;
+        if chunk_size == Some(0) {
+            bail!("chunkSize must be greater than 0");
+        }
+        let args = map
+            .0
+            .iter()
+            .filter(|(k, _)| !EVENT_OPTIONS.contains(&k.as_str()))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
 
         Ok(EventOptions {
             topic0,
@@ -35,51 +86,200 @@ impl TryFrom<&crate::interpreter::types::HashableIndexMap<String, Value>> for Ev
             topic3,
             from_block,
             to_block,
+            chunk_size,
+            args,
         })
     }
 }
 
-fn fetch_events<'a>(
-    env: &'a mut Env,
-    args: &'a [Value],
-    options: EventOptions,
-) -> BoxFuture<'a, Result<Value>> {
-    async move {
-        let mut filter = Filter::new();
-        if let Some(topic0) = options.topic0 {
-            filter = filter.event_signature(topic0);
-        }
-        if let Some(topic1) = options.topic1 {
-            filter = filter.topic1(topic1);
-        }
-        if let Some(topic2) = options.topic2 {
-            filter = filter.topic2(topic2);
-        }
-        if let Some(topic3) = options.topic3 {
-            filter = filter.topic3(topic3);
+impl EventOptions {
+    /// Fails on options that are neither filter options nor event arguments,
+    /// as arguments can only be matched when fetching a specific event
+    fn ensure_no_args(&self) -> Result<()> {
+        match self.args.keys().next() {
+            Some(key) => bail!("unexpected key {}", key),
+            None => Ok(()),
         }
-        if let Some(from_block) = options.from_block {
-            filter = filter.from_block(from_block);
-        } else {
-            filter = filter.from_block(0);
+    }
+}
+
+pub(super) fn build_filter(args: &[Value], options: &EventOptions) -> Result<Filter> {
+    let mut filter = Filter::new();
+    if let Some(topic0) = options.topic0 {
+        filter = filter.event_signature(topic0);
+    }
+    if let Some(topic1) = options.topic1 {
+        filter = filter.topic1(topic1);
+    }
+    if let Some(topic2) = options.topic2 {
+        filter = filter.topic2(topic2);
+    }
+    if let Some(topic3) = options.topic3 {
+        filter = filter.topic3(topic3);
+    }
+    if let Some(from_block) = options.from_block {
+        filter = filter.from_block(from_block);
+    } else {
+        filter = filter.from_block(0);
+    }
+    if let Some(to_block) = options.to_block {
+        filter = filter.to_block(to_block);
+    }
+
+    match args {
+        [Value::Addr(addr)] => filter = filter.address(*addr),
+        [Value::Array(addrs, ty_)] if ty_.as_ref() == &Type::Address => {
+            let addresses = addrs
+                .iter()
+                .map(|a| a.as_address())
+                .collect::<Result<Vec<_>>>()?;
+            filter = filter.address(addresses)
         }
-        if let Some(to_block) = options.to_block {
-            filter = filter.to_block(to_block);
+        _ => bail!("events.fetch: invalid arguments"),
+    }
+    Ok(filter)
+}
+
+pub(super) fn address_params() -> Vec<Vec<FunctionParam>> {
+    vec![
+        vec![FunctionParam::new("address", Type::Address)],
+        vec![FunctionParam::new(
+            "addresses",
+            Type::Array(Box::new(Type::Address)),
+        )],
+    ]
+}
+
+fn is_range_error(error: &str) -> bool {
+    let message = error.to_lowercase();
+    RANGE_ERROR_PATTERNS.iter().any(|p| message.contains(p))
+}
+
+/// Size of the block range suggested by some providers when rejecting a query, e.g.
+/// "query returned more than 10000 results. Try with this block range [0x10, 0x2F]."
+fn suggested_range_size(error: &str) -> Option<u64> {
+    let start = error.find('[')?;
+    let end = start + error[start..].find(']')?;
+    let (from, to) = error[start + 1..end].split_once(',')?;
+    let parse = |block: &str| u64::from_str_radix(block.trim().trim_start_matches("0x"), 16).ok();
+    parse(to)?.checked_sub(parse(from)?).map(|size| size + 1)
+}
+
+/// Fetches the logs between `from` and `to`. Whenever the provider rejects the range
+/// as too large, the shared chunk size is narrowed, so that the following chunks
+/// are directly requested with the smaller size, and the range is fetched again
+fn fetch_range<'a, P: Provider>(
+    provider: &'a P,
+    filter: &'a Filter,
+    chunk_size: &'a AtomicU64,
+    from: u64,
+    to: u64,
+) -> BoxFuture<'a, Result<Vec<Log>>> {
+    async move {
+        let range_filter = filter.clone().from_block(from).to_block(to);
+        match provider.get_logs(&range_filter).await {
+            Ok(logs) => Ok(logs),
+            Err(e) if from < to && is_range_error(&e.to_string()) => {
+                let size = suggested_range_size(&e.to_string())
+                    .unwrap_or((to - from + 1) / 2)
+                    .clamp(1, to - from);
+                let size = chunk_size.fetch_min(size, Ordering::Relaxed).min(size);
+                let mut logs = vec![];
+                for start in (from..=to).step_by(size as usize) {
+                    let end = to.min(start + size - 1);
+                    logs.extend(fetch_range(provider, filter, chunk_size, start, end).await?);
+                }
+                Ok(logs)
+            }
+            Err(e) => Err(anyhow!("blocks {} to {}: {}", from, to, e)),
         }
+    }
+    .boxed()
+}
 
-        match args {
-            [Value::Addr(addr)] => filter = filter.address(*addr),
-            [Value::Array(addrs, ty_)] if ty_.as_ref() == &Type::Address => {
-                let addresses = addrs
-                    .iter()
-                    .map(|a| a.as_address())
-                    .collect::<Result<Vec<_>>>()?;
-                filter = filter.address(addresses)
+/// Fetches the logs matching the filter in chunks of blocks, running a bounded number
+/// of requests concurrently.
+/// If a chunk fails after some others succeeded, the error contains the block to resume from.
+pub(super) async fn get_logs(
+    env: &Env,
+    filter: &Filter,
+    chunk_size: Option<u64>,
+) -> Result<Vec<Log>> {
+    let provider = env.get_provider();
+    let from_block = filter.get_from_block().unwrap_or(0);
+    let to_block = match filter.get_to_block() {
+        Some(to_block) => to_block,
+        None => provider.get_block_number().await?,
+    };
+    if from_block > to_block {
+        return Ok(vec![]);
+    }
+
+    let chunk_size = AtomicU64::new(chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE));
+    let show_progress = to_block - from_block >= chunk_size.load(Ordering::Relaxed);
+    let total = to_block - from_block + 1;
+
+    // chunks are created lazily so that they use the chunk size narrowed by previous ones
+    let size = &chunk_size;
+    let chunks = stream::unfold(Some(from_block), move |start| async move {
+        let start = start?;
+        let end = to_block.min(start.saturating_add(size.load(Ordering::Relaxed) - 1));
+        Some(((start, end), (end < to_block).then_some(end + 1)))
+    });
+    let mut results = chunks
+        .map(|(from, to)| {
+            let provider = &provider;
+            async move {
+                (
+                    from,
+                    to,
+                    fetch_range(provider, filter, size, from, to).await,
+                )
+            }
+        })
+        .buffered(MAX_CONCURRENT_REQUESTS);
+
+    let mut logs = vec![];
+    let mut done = false;
+    while let Some((from, to, result)) = results.next().await {
+        match result {
+            Ok(chunk_logs) => logs.extend(chunk_logs),
+            Err(e) if !done => return Err(e),
+            Err(e) => {
+                eprintln!();
+                bail!(
+                    "failed to fetch logs after block {}, resume with {{fromBlock: {}}}: {}",
+                    from - 1,
+                    from,
+                    e
+                );
             }
-            _ => bail!("events.fetch: invalid arguments"),
         }
+        done = true;
+        if show_progress {
+            let fetched = to - from_block + 1;
+            eprint!(
+                "\rfetched {}/{} blocks ({} logs)",
+                fetched,
+                total,
+                logs.len()
+            );
+        }
+    }
+    if show_progress {
+        eprintln!();
+    }
+    Ok(logs)
+}
 
-        let logs = env.get_provider().get_logs(&filter).await?;
+fn fetch_events<'a>(
+    env: &'a mut Env,
+    args: &'a [Value],
+    options: EventOptions,
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        let filter = build_filter(args, &options)?;
+        let logs = get_logs(env, &filter, options.chunk_size).await?;
         let parsed_logs = logs
             .into_iter()
             .map(|log| utils::log_to_value(env, log))
@@ -89,6 +289,66 @@ fn fetch_events<'a>(
     .boxed()
 }
 
+/// Calls `func` with each new log matching the filter, subscribing to logs
+/// when the transport supports it and polling for new blocks otherwise
+async fn watch_logs(
+    env: &mut Env,
+    filter: Filter,
+    options: &EventOptions,
+    func: &Function,
+) -> Result<()> {
+    let provider = env.get_provider();
+    if options.from_block.is_none() && provider.client().pubsub_frontend().is_some() {
+        let mut logs = provider.subscribe_logs(&filter).await?.into_stream();
+        while let Some(log) = logs.next().await {
+            let value = utils::log_to_value(env, log)?;
+            func.execute(env, &[value]).await?;
+        }
+        return Ok(());
+    }
+
+    let interval = provider.client().poll_interval();
+    let mut next_block = match options.from_block {
+        Some(block) => block,
+        None => provider.get_block_number().await? + 1,
+    };
+    loop {
+        let latest = provider.get_block_number().await?;
+        if latest >= next_block {
+            // catching up from `fromBlock` can span many blocks, so it is fetched in chunks
+            let range_filter = filter.clone().from_block(next_block).to_block(latest);
+            for log in get_logs(env, &range_filter, options.chunk_size).await? {
+                let value = utils::log_to_value(env, log)?;
+                func.execute(env, &[value]).await?;
+            }
+            next_block = latest + 1;
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+fn watch_events<'a>(
+    env: &'a mut Env,
+    args: &'a [Value],
+    options: EventOptions,
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        let (addresses, func) = match args {
+            [addresses, Value::Func(func)] => (addresses, func),
+            _ => bail!("events.watch: invalid arguments"),
+        };
+        let filter = build_filter(std::slice::from_ref(addresses), &options)?;
+        eprintln!("watching events, press Ctrl-C to stop");
+        let depth = env.scope_depth();
+        match utils::run_until_interrupted(watch_logs(env, filter, &options, func)).await {
+            Some(result) => result?,
+            None => env.truncate_scopes(depth),
+        }
+        Ok(Value::Null)
+    }
+    .boxed()
+}
+
 #[derive(Debug)]
 struct FetchEvents;
 
@@ -97,20 +357,8 @@ impl FunctionDef for FetchEvents {
         "fetch".to_string()
     }
 
-    fn get_valid_args(
-        &self,
-        _receiver: &Option<Value>,
-    ) -> Vec<Vec<crate::interpreter::functions::FunctionParam>> {
-        vec![
-            vec![crate::interpreter::functions::FunctionParam::new(
-                "address",
-                Type::Address,
-            )],
-            vec![crate::interpreter::functions::FunctionParam::new(
-                "addresses",
-                Type::Array(Box::new(Type::Address)),
-            )],
-        ]
+    fn get_valid_args(&self, _receiver: &Option<Value>) -> Vec<Vec<FunctionParam>> {
+        address_params()
     }
 
     fn is_property(&self) -> bool {
@@ -121,16 +369,86 @@ impl FunctionDef for FetchEvents {
         &'a self,
         env: &'a mut Env,
         values: &'a [Value],
-        options: &'a crate::interpreter::types::HashableIndexMap<String, Value>,
+        options: &'a HashableIndexMap<String, Value>,
     ) -> BoxFuture<'a, Result<Value>> {
         async move {
-            let parsed_opts = options.try_into()?;
+            let parsed_opts: EventOptions = options.try_into()?;
+            parsed_opts.ensure_no_args()?;
             fetch_events(env, &values[1..], parsed_opts).await
         }
         .boxed()
     }
 }
 
+#[derive(Debug)]
+struct WatchEvents;
+
+impl FunctionDef for WatchEvents {
+    fn name(&self) -> String {
+        "watch".to_string()
+    }
+
+    fn get_valid_args(&self, _receiver: &Option<Value>) -> Vec<Vec<FunctionParam>> {
+        address_params()
+            .into_iter()
+            .map(|mut params| {
+                params.push(FunctionParam::new("f", Type::Function));
+                params
+            })
+            .collect()
+    }
+
+    fn is_property(&self) -> bool {
+        false
+    }
+
+    fn execute<'a>(
+        &'a self,
+        env: &'a mut Env,
+        values: &'a [Value],
+        options: &'a HashableIndexMap<String, Value>,
+    ) -> BoxFuture<'a, Result<Value>> {
+        async move {
+            let parsed_opts: EventOptions = options.try_into()?;
+            parsed_opts.ensure_no_args()?;
+            watch_events(env, &values[1..], parsed_opts).await
+        }
+        .boxed()
+    }
+}
+
 lazy_static! {
     pub static ref FETCH_EVENTS: Arc<dyn FunctionDef> = Arc::new(FetchEvents);
+    pub static ref WATCH_EVENTS: Arc<dyn FunctionDef> = Arc::new(WatchEvents);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_range_error() {
+        assert!(is_range_error(
+            "server returned an error response: error code -32005: query returned more than 10000 results"
+        ));
+        assert!(is_range_error("eth_getLogs is limited to a 10,000 range"));
+        assert!(!is_range_error(
+            "insufficient funds: balance is more than the cost"
+        ));
+    }
+
+    #[test]
+    fn test_suggested_range_size() {
+        assert_eq!(
+            suggested_range_size(
+                "query returned more than 10000 results. Try with this block range [0x10, 0x2F]."
+            ),
+            Some(32)
+        );
+        assert_eq!(
+            suggested_range_size("query returned more than 10000 results"),
+            None
+        );
+        assert_eq!(suggested_range_size("invalid range [0x20, 0x10]"), None);
+    }
 }
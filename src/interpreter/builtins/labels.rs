@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use lazy_static::lazy_static;
+
+use crate::interpreter::{
+    functions::{FunctionDef, FunctionParam, SyncMethod, SyncProperty},
+    types::HashableIndexMap,
+    Env, Type, Value,
+};
+
+fn set_label(env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    match args {
+        [Value::Addr(addr), Value::Str(label)] => {
+            env.get_address_book_mut()?.set(*addr, label)?;
+            Ok(Value::Null)
+        }
+        _ => bail!("set function expects an address and a label as arguments"),
+    }
+}
+
+fn get_address(env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    match args {
+        [Value::Str(label)] => env
+            .get_address_book()
+            .and_then(|book| book.get_address(label))
+            .map(Value::Addr)
+            .ok_or(anyhow!("no address labelled {}", label)),
+        _ => bail!("get function expects a label as argument"),
+    }
+}
+
+fn remove_label(env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    match args {
+        [Value::Str(label)] => {
+            env.get_address_book_mut()?.remove(label)?;
+            Ok(Value::Null)
+        }
+        _ => bail!("remove function expects a label as argument"),
+    }
+}
+
+fn list_labels(env: &Env, _receiver: &Value) -> Result<Value> {
+    let labels = env
+        .get_address_book()
+        .map(|book| book.list())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(label, addr)| (Value::Str(label), Value::Addr(addr)))
+        .collect();
+    Ok(Value::Mapping(
+        HashableIndexMap(labels),
+        Box::new(Type::String),
+        Box::new(Type::Address),
+    ))
+}
+
+lazy_static! {
+    pub static ref LABELS_SET: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "set",
+        set_label,
+        vec![vec![
+            FunctionParam::new("address", Type::Address),
+            FunctionParam::new("label", Type::String)
+        ]]
+    );
+    pub static ref LABELS_GET: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "get",
+        get_address,
+        vec![vec![FunctionParam::new("label", Type::String)]]
+    );
+    pub static ref LABELS_REMOVE: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "remove",
+        remove_label,
+        vec![vec![FunctionParam::new("label", Type::String)]]
+    );
+    pub static ref LABELS_LIST: Arc<dyn FunctionDef> = SyncProperty::arc("list", list_labels);
+}
@@ -8,18 +8,24 @@ mod abi;
 mod accounts;
 mod address;
 mod block;
+mod chain;
 mod concat;
 mod console;
 mod contract_function;
+mod ens;
 mod event;
 mod events;
 mod format;
 mod fs;
 mod iterable;
 mod json;
+mod labels;
 mod misc;
 mod numeric;
+mod permit;
 mod repl;
+mod signature;
+mod storage;
 mod transaction;
 mod vm;
 
@@ -46,10 +52,16 @@ lazy_static! {
             Value::TypeObject(Type::Transaction),
         );
         m.insert("abi".to_string(), Value::TypeObject(Type::Abi));
+        m.insert("ens".to_string(), Value::TypeObject(Type::Ens));
+        m.insert("labels".to_string(), Value::TypeObject(Type::Labels));
+        m.insert("storage".to_string(), Value::TypeObject(Type::Storage));
+        m.insert("sig".to_string(), Value::TypeObject(Type::Sig));
+        m.insert("permit".to_string(), Value::TypeObject(Type::Permit));
 
         let funcs: Vec<(&str, Arc<dyn FunctionDef>)> = vec![
             ("format", format::FORMAT_FUNCTION.clone()),
             ("keccak256", misc::KECCAK256.clone()),
+            ("ecrecover", signature::ECRECOVER.clone()),
             ("type", misc::GET_TYPE.clone()),
         ];
         for (name, func) in funcs {
@@ -119,8 +131,16 @@ lazy_static! {
         transaction_methods.insert("input".to_string(), transaction::TX_GET_INPUT_DATA.clone());
         transaction_methods.insert("from".to_string(), transaction::TX_GET_FROM.clone());
         transaction_methods.insert("to".to_string(), transaction::TX_GET_TO.clone());
+        transaction_methods.insert("speedUp".to_string(), transaction::TX_SPEED_UP.clone());
+        transaction_methods.insert("cancel".to_string(), transaction::TX_CANCEL.clone());
+        transaction_methods.insert("on".to_string(), chain::CHAIN_ON.clone());
         m.insert(NonParametricType::Transaction, transaction_methods);
 
+        let mut contract_methods = HashMap::new();
+        contract_methods.insert("on".to_string(), chain::CHAIN_ON.clone());
+        contract_methods.insert("storage".to_string(), storage::CONTRACT_STORAGE.clone());
+        m.insert(NonParametricType::Contract, contract_methods);
+
         let mut mapping_methods = HashMap::new();
         mapping_methods.insert("format".to_string(), format::NON_NUM_FORMAT.clone());
         mapping_methods.insert("keys".to_string(), misc::MAPPING_KEYS.clone());
@@ -150,11 +170,62 @@ lazy_static! {
         abi_methods.insert("fetch".to_string(), abi::ABI_FETCH.clone());
         m.insert(NonParametricType::Abi, abi_methods);
 
+        let mut ens_methods = HashMap::new();
+        ens_methods.insert("resolve".to_string(), ens::ENS_RESOLVE.clone());
+        ens_methods.insert("lookup".to_string(), ens::ENS_LOOKUP.clone());
+        ens_methods.insert("namehash".to_string(), ens::ENS_NAMEHASH.clone());
+        m.insert(NonParametricType::Ens, ens_methods);
+
+        let mut labels_methods = HashMap::new();
+        labels_methods.insert("set".to_string(), labels::LABELS_SET.clone());
+        labels_methods.insert("get".to_string(), labels::LABELS_GET.clone());
+        labels_methods.insert("remove".to_string(), labels::LABELS_REMOVE.clone());
+        labels_methods.insert("list".to_string(), labels::LABELS_LIST.clone());
+        m.insert(NonParametricType::Labels, labels_methods);
+
+        let mut storage_methods = HashMap::new();
+        storage_methods.insert(
+            "mappingSlot".to_string(),
+            storage::STORAGE_MAPPING_SLOT.clone(),
+        );
+        storage_methods.insert("arraySlot".to_string(), storage::STORAGE_ARRAY_SLOT.clone());
+        storage_methods.insert("erc7201".to_string(), storage::STORAGE_ERC7201.clone());
+        storage_methods.insert("unpack".to_string(), storage::STORAGE_UNPACK.clone());
+        m.insert(NonParametricType::Storage, storage_methods);
+
+        let mut sig_methods = HashMap::new();
+        sig_methods.insert("recover".to_string(), signature::SIG_RECOVER.clone());
+        sig_methods.insert("split".to_string(), signature::SIG_SPLIT.clone());
+        sig_methods.insert("parse".to_string(), signature::SIG_PARSE.clone());
+        sig_methods.insert("join".to_string(), signature::SIG_JOIN.clone());
+        sig_methods.insert("verify".to_string(), signature::SIG_VERIFY.clone());
+        m.insert(NonParametricType::Sig, sig_methods);
+
+        let mut permit_methods = HashMap::new();
+        permit_methods.insert("sign".to_string(), permit::PERMIT_SIGN.clone());
+        permit_methods.insert(
+            "signPermit2".to_string(),
+            permit::PERMIT_SIGN_PERMIT2.clone(),
+        );
+        permit_methods.insert(
+            "signPermit2Batch".to_string(),
+            permit::PERMIT_SIGN_PERMIT2_BATCH.clone(),
+        );
+        m.insert(NonParametricType::Permit, permit_methods);
+
+        let mut transaction_methods = HashMap::new();
+        transaction_methods.insert("send".to_string(), transaction::TX_SEND.clone());
+        transaction_methods.insert("sign".to_string(), transaction::TX_SIGN.clone());
+        transaction_methods.insert("sendRaw".to_string(), transaction::TX_SEND_RAW.clone());
+        m.insert(NonParametricType::Transaction, transaction_methods);
+
         let mut block_methods = HashMap::new();
         block_methods.insert("chainid".to_string(), block::BLOCK_CHAIN_ID.clone());
         block_methods.insert("basefee".to_string(), block::BLOCK_BASE_FEE.clone());
         block_methods.insert("number".to_string(), block::BLOCK_NUMBER.clone());
         block_methods.insert("timestamp".to_string(), block::BLOCK_TIMESTAMP.clone());
+        block_methods.insert("watch".to_string(), block::BLOCK_WATCH.clone());
+        block_methods.insert("feeHistory".to_string(), block::BLOCK_FEE_HISTORY.clone());
         m.insert(NonParametricType::Block, block_methods);
 
         let mut console_methods = HashMap::new();
@@ -171,6 +242,7 @@ lazy_static! {
 
         let mut event_methods = HashMap::new();
         event_methods.insert("selector".to_string(), event::EVENT_SELECTOR.clone());
+        event_methods.insert("fetch".to_string(), event::EVENT_FETCH.clone());
         m.insert(NonParametricType::Event, event_methods);
 
         let mut contract_function_methods = HashMap::new();
@@ -185,6 +257,7 @@ lazy_static! {
 
         let mut events_methods = HashMap::new();
         events_methods.insert("fetch".to_string(), events::FETCH_EVENTS.clone());
+        events_methods.insert("watch".to_string(), events::WATCH_EVENTS.clone());
         m.insert(NonParametricType::Events, events_methods);
 
         let mut vm_methods = HashMap::new();
@@ -198,6 +271,8 @@ lazy_static! {
         vm_methods.insert("getEnv".to_string(), vm::VM_ENV.clone());
         vm_methods.insert("block".to_string(), vm::VM_BLOCK.clone());
         vm_methods.insert("connected".to_string(), vm::VM_IS_CONNECTED.clone());
+        vm_methods.insert("implementation".to_string(), vm::VM_IMPLEMENTATION.clone());
+        vm_methods.insert("proxy".to_string(), vm::VM_PROXY.clone());
         m.insert(NonParametricType::Vm, vm_methods);
 
         let mut repl_methods = HashMap::new();
@@ -205,12 +280,31 @@ lazy_static! {
         repl_methods.insert("types".to_string(), repl::REPL_LIST_TYPES.clone());
         repl_methods.insert("debug".to_string(), repl::REPL_DEBUG.clone());
         repl_methods.insert("exec".to_string(), repl::REPL_EXEC.clone());
+        repl_methods.insert("cache".to_string(), repl::REPL_CACHE.clone());
+        repl_methods.insert("fees".to_string(), repl::REPL_FEES.clone());
         m.insert(NonParametricType::Repl, repl_methods);
 
+        let mut cache_methods = HashMap::new();
+        cache_methods.insert("clear".to_string(), repl::CACHE_CLEAR.clone());
+        cache_methods.insert("stats".to_string(), repl::CACHE_STATS.clone());
+        m.insert(NonParametricType::Cache, cache_methods);
+
         let mut account_methods = HashMap::new();
         account_methods.insert("current".to_string(), accounts::ACCOUNT_CURRENT.clone());
         account_methods.insert("loaded".to_string(), accounts::ACCOUNT_GET_LOADED.clone());
         account_methods.insert("select".to_string(), accounts::ACCOUNT_SELECT.clone());
+        account_methods.insert(
+            "signMessage".to_string(),
+            accounts::ACCOUNT_SIGN_MESSAGE.clone(),
+        );
+        account_methods.insert(
+            "signTypedData".to_string(),
+            accounts::ACCOUNT_SIGN_TYPED_DATA.clone(),
+        );
+        account_methods.insert(
+            "signAuthorization".to_string(),
+            accounts::ACCOUNT_SIGN_AUTHORIZATION.clone(),
+        );
         account_methods.insert(
             "loadPrivateKey".to_string(),
             accounts::ACCOUNT_LOAD_PRIVATE_KEY.clone(),
@@ -223,6 +317,22 @@ lazy_static! {
             "loadKeystore".to_string(),
             accounts::ACCOUNT_LOAD_KEYSTORE.clone(),
         );
+        account_methods.insert(
+            "loadRemoteSigner".to_string(),
+            accounts::ACCOUNT_LOAD_REMOTE_SIGNER.clone(),
+        );
+        account_methods.insert(
+            "createKeystore".to_string(),
+            accounts::ACCOUNT_CREATE_KEYSTORE.clone(),
+        );
+        account_methods.insert(
+            "importKeystore".to_string(),
+            accounts::ACCOUNT_IMPORT_KEYSTORE.clone(),
+        );
+        account_methods.insert(
+            "exportAddress".to_string(),
+            accounts::ACCOUNT_EXPORT_ADDRESS.clone(),
+        );
         account_methods.insert(
             "listLedgerWallets".to_string(),
             accounts::ACCOUNT_LIST_LEDGER_WALLETS.clone(),
@@ -231,6 +341,15 @@ lazy_static! {
             "loadLedger".to_string(),
             accounts::ACCOUNT_LOAD_LEDGER.clone(),
         );
+        account_methods.insert(
+            "loadMnemonic".to_string(),
+            accounts::ACCOUNT_LOAD_MNEMONIC.clone(),
+        );
+        account_methods.insert(
+            "listMnemonicWallets".to_string(),
+            accounts::ACCOUNT_LIST_MNEMONIC_WALLETS.clone(),
+        );
+        account_methods.insert("pending".to_string(), accounts::ACCOUNT_PENDING.clone());
         account_methods.insert("alias".to_string(), accounts::ACCOUNT_ALIAS.clone());
         m.insert(NonParametricType::Accounts, account_methods);
 
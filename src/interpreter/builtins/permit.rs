@@ -0,0 +1,341 @@
+use std::sync::Arc;
+
+use alloy::{
+    dyn_abi::{Resolver, TypedData},
+    primitives::{address, Address, Signature, U256},
+    signers::Signer,
+    sol,
+    sol_types::Eip712Domain,
+};
+use anyhow::{bail, Result};
+use futures::{future::BoxFuture, FutureExt};
+use indexmap::IndexMap;
+use lazy_static::lazy_static;
+
+use crate::interpreter::{
+    env::MessageSigner,
+    functions::{AsyncMethod, FunctionDef, FunctionParam},
+    types::HashableIndexMap,
+    Env, Type, Value,
+};
+
+/// Uniswap Permit2, deployed at the same address on all chains
+const PERMIT2: Address = address!("000000000022D473030F116dDEE9F6B43aC78BA3");
+
+const PERMIT_TYPE: &str =
+    "Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)";
+const PERMIT_DETAILS_TYPE: &str =
+    "PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)";
+
+sol! {
+    #[sol(rpc)]
+    interface IERC20Permit {
+        function name() external view returns (string);
+        function version() external view returns (string);
+        function nonces(address owner) external view returns (uint256);
+        function DOMAIN_SEPARATOR() external view returns (bytes32);
+        function eip712Domain() external view returns (
+            bytes1 fields,
+            string name,
+            string version,
+            uint256 chainId,
+            address verifyingContract,
+            bytes32 salt,
+            uint256[] extensions
+        );
+    }
+
+    #[sol(rpc)]
+    interface IPermit2 {
+        function allowance(address owner, address token, address spender)
+            external
+            view
+            returns (uint160 amount, uint48 expiration, uint48 nonce);
+    }
+}
+
+fn named_tuple(name: &str, fields: Vec<(&str, Value)>) -> Value {
+    let fields = fields
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect::<IndexMap<_, _>>();
+    Value::NamedTuple(name.to_string(), HashableIndexMap(fields))
+}
+
+/// Returns the EIP-712 domain of a token, using EIP-5267 when supported and
+/// otherwise finding the version matching the token `DOMAIN_SEPARATOR`
+async fn get_token_domain(env: &Env, token: Address) -> Result<Eip712Domain> {
+    let contract = IERC20Permit::new(token, env.get_provider());
+    if let Ok(domain) = contract.eip712Domain().block(env.block()).call().await {
+        let fields = domain.fields[0];
+        return Ok(Eip712Domain::new(
+            (fields & 0x01 != 0).then(|| domain.name.into()),
+            (fields & 0x02 != 0).then(|| domain.version.into()),
+            (fields & 0x04 != 0).then_some(domain.chainId),
+            (fields & 0x08 != 0).then_some(domain.verifyingContract),
+            (fields & 0x10 != 0).then_some(domain.salt),
+        ));
+    }
+
+    let name = contract.name().block(env.block()).call().await?;
+    let separator = contract
+        .DOMAIN_SEPARATOR()
+        .block(env.block())
+        .call()
+        .await?;
+    let chain_id = U256::from(env.get_chain_id().await?);
+    let mut versions = vec!["1".to_string(), "2".to_string()];
+    if let Ok(version) = contract.version().block(env.block()).call().await {
+        versions.insert(0, version);
+    }
+    for version in versions {
+        let domain = Eip712Domain::new(
+            Some(name.clone().into()),
+            Some(version.into()),
+            Some(chain_id),
+            Some(token),
+            None,
+        );
+        if domain.separator() == separator {
+            return Ok(domain);
+        }
+    }
+    bail!("could not determine the EIP-712 domain of {}", token)
+}
+
+async fn get_permit2_domain(env: &Env) -> Result<Eip712Domain> {
+    let chain_id = U256::from(env.get_chain_id().await?);
+    Ok(Eip712Domain::new(
+        Some("Permit2".into()),
+        None,
+        Some(chain_id),
+        Some(PERMIT2),
+        None,
+    ))
+}
+
+async fn sign_typed_data(
+    signer: &MessageSigner,
+    domain: Eip712Domain,
+    types: &str,
+    message: &Value,
+) -> Result<Signature> {
+    let (primary_type, _) = types.split_once('(').unwrap_or_default();
+    let mut resolver = Resolver::default();
+    resolver.ingest_string(types)?;
+    let typed_data = TypedData {
+        domain,
+        resolver,
+        primary_type: primary_type.to_string(),
+        message: serde_json::to_value(message)?,
+    };
+    Ok(signer.sign_dynamic_typed_data(&typed_data).await?)
+}
+
+fn sign_permit<'a>(
+    env: &'a mut Env,
+    _receiver: &'a Value,
+    args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        let (token, spender, value, deadline) = match args {
+            [Value::Addr(token), Value::Addr(spender), Value::Uint(value, _), Value::Uint(deadline, _)] => {
+                (*token, *spender, *value, *deadline)
+            }
+            _ => bail!("sign function expects token, spender, value and deadline as arguments"),
+        };
+        let signer = env.get_signer()?;
+        let owner = signer.address();
+        let domain = get_token_domain(env, token).await?;
+        let nonce = IERC20Permit::new(token, env.get_provider())
+            .nonces(owner)
+            .block(env.block())
+            .call()
+            .await?;
+
+        let message = named_tuple(
+            "Permit",
+            vec![
+                ("owner", Value::Addr(owner)),
+                ("spender", Value::Addr(spender)),
+                ("value", Value::Uint(value, 256)),
+                ("nonce", Value::Uint(nonce, 256)),
+                ("deadline", Value::Uint(deadline, 256)),
+            ],
+        );
+        let signature = sign_typed_data(&signer, domain, PERMIT_TYPE, &message).await?;
+
+        Ok(named_tuple(
+            "Permit",
+            vec![
+                ("owner", Value::Addr(owner)),
+                ("spender", Value::Addr(spender)),
+                ("value", Value::Uint(value, 256)),
+                ("deadline", Value::Uint(deadline, 256)),
+                ("v", Value::Uint(U256::from(27 + signature.v() as u8), 8)),
+                ("r", Value::FixBytes(signature.r().into(), 32)),
+                ("s", Value::FixBytes(signature.s().into(), 32)),
+            ],
+        ))
+    }
+    .boxed()
+}
+
+async fn permit_details(
+    env: &Env,
+    owner: Address,
+    token: Address,
+    spender: Address,
+    amount: U256,
+    expiration: U256,
+) -> Result<Value> {
+    let allowance = IPermit2::new(PERMIT2, env.get_provider())
+        .allowance(owner, token, spender)
+        .block(env.block())
+        .call()
+        .await?;
+    Ok(named_tuple(
+        "PermitDetails",
+        vec![
+            ("token", Value::Addr(token)),
+            ("amount", Value::Uint(amount, 160)),
+            ("expiration", Value::Uint(expiration, 48)),
+            (
+                "nonce",
+                Value::Uint(U256::from(allowance.nonce.to::<u64>()), 48),
+            ),
+        ],
+    ))
+}
+
+fn sign_permit2<'a>(
+    env: &'a mut Env,
+    _receiver: &'a Value,
+    args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        let (token, spender, amount, expiration, sig_deadline) = match args {
+            [Value::Addr(token), Value::Addr(spender), Value::Uint(amount, _), Value::Uint(expiration, _), Value::Uint(sig_deadline, _)] => {
+                (*token, *spender, *amount, *expiration, *sig_deadline)
+            }
+            _ => bail!("signPermit2 function expects token, spender, amount, expiration and sigDeadline as arguments"),
+        };
+        let signer = env.get_signer()?;
+        let owner = signer.address();
+        let details = permit_details(env, owner, token, spender, amount, expiration).await?;
+
+        let permit_single = named_tuple(
+            "PermitSingle",
+            vec![
+                ("details", details),
+                ("spender", Value::Addr(spender)),
+                ("sigDeadline", Value::Uint(sig_deadline, 256)),
+            ],
+        );
+        let types = format!(
+            "PermitSingle(PermitDetails details,address spender,uint256 sigDeadline){}",
+            PERMIT_DETAILS_TYPE
+        );
+        let domain = get_permit2_domain(env).await?;
+        let signature = sign_typed_data(&signer, domain, &types, &permit_single).await?;
+
+        Ok(named_tuple(
+            "Permit2",
+            vec![
+                ("owner", Value::Addr(owner)),
+                ("permitSingle", permit_single),
+                ("signature", Value::Bytes(signature.as_bytes().to_vec())),
+            ],
+        ))
+    }
+    .boxed()
+}
+
+fn sign_permit2_batch<'a>(
+    env: &'a mut Env,
+    _receiver: &'a Value,
+    args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        let (tokens, spender, amounts, expiration, sig_deadline) = match args {
+            [Value::Array(tokens, _), Value::Addr(spender), Value::Array(amounts, _), Value::Uint(expiration, _), Value::Uint(sig_deadline, _)] => {
+                (tokens, *spender, amounts, *expiration, *sig_deadline)
+            }
+            _ => bail!("signPermit2Batch function expects tokens, spender, amounts, expiration and sigDeadline as arguments"),
+        };
+        if tokens.is_empty() {
+            bail!("signPermit2Batch: tokens cannot be empty");
+        }
+        if tokens.len() != amounts.len() {
+            bail!("signPermit2Batch: tokens and amounts must have the same length");
+        }
+        let signer = env.get_signer()?;
+        let owner = signer.address();
+        let mut details = vec![];
+        for (token, amount) in tokens.iter().zip(amounts) {
+            let (token, amount) = (token.as_address()?, amount.as_u256()?);
+            details.push(permit_details(env, owner, token, spender, amount, expiration).await?);
+        }
+
+        let details_type = details[0].get_type();
+        let permit_batch = named_tuple(
+            "PermitBatch",
+            vec![
+                ("details", Value::Array(details, Box::new(details_type))),
+                ("spender", Value::Addr(spender)),
+                ("sigDeadline", Value::Uint(sig_deadline, 256)),
+            ],
+        );
+        let types = format!(
+            "PermitBatch(PermitDetails[] details,address spender,uint256 sigDeadline){}",
+            PERMIT_DETAILS_TYPE
+        );
+        let domain = get_permit2_domain(env).await?;
+        let signature = sign_typed_data(&signer, domain, &types, &permit_batch).await?;
+
+        Ok(named_tuple(
+            "Permit2",
+            vec![
+                ("owner", Value::Addr(owner)),
+                ("permitBatch", permit_batch),
+                ("signature", Value::Bytes(signature.as_bytes().to_vec())),
+            ],
+        ))
+    }
+    .boxed()
+}
+
+lazy_static! {
+    pub static ref PERMIT_SIGN: Arc<dyn FunctionDef> = AsyncMethod::arc(
+        "sign",
+        sign_permit,
+        vec![vec![
+            FunctionParam::new("token", Type::Address),
+            FunctionParam::new("spender", Type::Address),
+            FunctionParam::new("value", Type::Uint(256)),
+            FunctionParam::new("deadline", Type::Uint(256))
+        ]]
+    );
+    pub static ref PERMIT_SIGN_PERMIT2: Arc<dyn FunctionDef> = AsyncMethod::arc(
+        "signPermit2",
+        sign_permit2,
+        vec![vec![
+            FunctionParam::new("token", Type::Address),
+            FunctionParam::new("spender", Type::Address),
+            FunctionParam::new("amount", Type::Uint(160)),
+            FunctionParam::new("expiration", Type::Uint(48)),
+            FunctionParam::new("sigDeadline", Type::Uint(256))
+        ]]
+    );
+    pub static ref PERMIT_SIGN_PERMIT2_BATCH: Arc<dyn FunctionDef> = AsyncMethod::arc(
+        "signPermit2Batch",
+        sign_permit2_batch,
+        vec![vec![
+            FunctionParam::new("tokens", Type::Array(Box::new(Type::Address))),
+            FunctionParam::new("spender", Type::Address),
+            FunctionParam::new("amounts", Type::Array(Box::new(Type::Uint(160)))),
+            FunctionParam::new("expiration", Type::Uint(48)),
+            FunctionParam::new("sigDeadline", Type::Uint(256))
+        ]]
+    );
+}
@@ -1,10 +1,17 @@
 use std::{process::Command, sync::Arc};
 
+use alloy::primitives::U256;
+
 use anyhow::{anyhow, bail, Result};
+use futures::{future::BoxFuture, FutureExt};
 use lazy_static::lazy_static;
 
+use indexmap::IndexMap;
+
 use crate::interpreter::{
+    fees::FeeConfig,
     functions::{FunctionDef, FunctionParam, SyncMethod, SyncProperty},
+    types::HashableIndexMap,
     Env, Type, Value,
 };
 
@@ -50,6 +57,98 @@ fn exec(_env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
     Ok(code.into())
 }
 
+fn cache(_env: &Env, _receiver: &Value) -> Result<Value> {
+    Ok(Value::TypeObject(Type::Cache))
+}
+
+fn cache_clear(env: &mut Env, _receiver: &Value, _args: &[Value]) -> Result<Value> {
+    env.rpc_cache().clear()?;
+    Ok(Value::Null)
+}
+
+fn cache_stats(env: &Env, _receiver: &Value) -> Result<Value> {
+    let (entries, size) = env.rpc_cache().stats()?;
+    let mut fields = IndexMap::new();
+    fields.insert("entries".to_string(), entries.into());
+    fields.insert("size".to_string(), size.into());
+    Ok(Value::NamedTuple(
+        "CacheStats".to_string(),
+        HashableIndexMap(fields),
+    ))
+}
+
+fn fee_config_to_value(config: &FeeConfig) -> Value {
+    let mut fields = IndexMap::new();
+    fields.insert(
+        "strategy".to_string(),
+        config
+            .strategy
+            .map(|s| Value::Str(s.to_string()))
+            .unwrap_or(Value::Null),
+    );
+    fields.insert(
+        "maxFeeCap".to_string(),
+        config.max_fee_cap.map(Value::from).unwrap_or(Value::Null),
+    );
+    fields.insert(
+        "gasLimitMultiplier".to_string(),
+        config
+            .gas_limit_multiplier
+            .map(|(digits, decimals)| Value::Decimal(digits, decimals))
+            .unwrap_or(Value::Null),
+    );
+    Value::NamedTuple("FeeConfig".to_string(), HashableIndexMap(fields))
+}
+
+/// Updates the fee configuration from the call options, `null` resetting a setting
+#[derive(Debug)]
+struct Fees;
+
+impl FunctionDef for Fees {
+    fn name(&self) -> String {
+        "fees".to_string()
+    }
+
+    fn get_valid_args(&self, _receiver: &Option<Value>) -> Vec<Vec<FunctionParam>> {
+        vec![vec![]]
+    }
+
+    fn is_property(&self) -> bool {
+        false
+    }
+
+    fn execute<'a>(
+        &'a self,
+        env: &'a mut Env,
+        _values: &'a [Value],
+        options: &'a HashableIndexMap<String, Value>,
+    ) -> BoxFuture<'a, Result<Value>> {
+        async move {
+            let mut config = env.get_fee_config().clone();
+            for (k, v) in options.0.iter() {
+                match (k.as_str(), v) {
+                    ("strategy", Value::Null) => config.strategy = None,
+                    ("strategy", v) => config.strategy = Some(v.as_string()?.parse()?),
+                    ("maxFeeCap", Value::Null) => config.max_fee_cap = None,
+                    ("maxFeeCap", v) => config.max_fee_cap = Some(v.as_u128()?),
+                    ("gasLimitMultiplier", Value::Null) => config.gas_limit_multiplier = None,
+                    ("gasLimitMultiplier", v) => {
+                        let (digits, decimals) = v.as_decimal()?;
+                        if digits < U256::from(10).pow(U256::from(decimals)) {
+                            bail!("gasLimitMultiplier must be at least 1");
+                        }
+                        config.gas_limit_multiplier = Some((digits, decimals));
+                    }
+                    _ => bail!("unexpected key {}", k),
+                }
+            }
+            env.set_fee_config(config);
+            Ok(fee_config_to_value(env.get_fee_config()))
+        }
+        .boxed()
+    }
+}
+
 lazy_static! {
     pub static ref REPL_LIST_VARS: Arc<dyn FunctionDef> = SyncProperty::arc("vars", list_vars);
     pub static ref REPL_LIST_TYPES: Arc<dyn FunctionDef> = SyncProperty::arc("types", list_types);
@@ -58,9 +157,14 @@ lazy_static! {
         debug,
         vec![vec![], vec![FunctionParam::new("debug", Type::Bool)]]
     );
+    pub static ref REPL_CACHE: Arc<dyn FunctionDef> = SyncProperty::arc("cache", cache);
+    pub static ref CACHE_CLEAR: Arc<dyn FunctionDef> =
+        SyncMethod::arc("clear", cache_clear, vec![vec![]]);
+    pub static ref CACHE_STATS: Arc<dyn FunctionDef> = SyncProperty::arc("stats", cache_stats);
     pub static ref REPL_EXEC: Arc<dyn FunctionDef> = SyncMethod::arc(
         "exec",
         exec,
         vec![vec![FunctionParam::new("command", Type::String)]]
     );
+    pub static ref REPL_FEES: Arc<dyn FunctionDef> = Arc::new(Fees);
 }
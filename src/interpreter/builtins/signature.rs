@@ -0,0 +1,238 @@
+use std::sync::Arc;
+
+use alloy::{
+    primitives::{fixed_bytes, Address, FixedBytes, Signature, B256, U256},
+    providers::Provider,
+    sol,
+};
+use anyhow::{anyhow, bail, Result};
+use futures::{future::BoxFuture, FutureExt};
+use lazy_static::lazy_static;
+
+use crate::interpreter::{
+    functions::{AsyncMethod, FunctionDef, FunctionParam, SyncFunction, SyncMethod},
+    types::HashableIndexMap,
+    Env, Type, Value,
+};
+
+/// Value returned by `isValidSignature` for valid signatures, as defined in EIP-1271
+const ERC1271_MAGIC_VALUE: FixedBytes<4> = fixed_bytes!("1626ba7e");
+
+sol! {
+    #[sol(rpc)]
+    interface IERC1271 {
+        function isValidSignature(bytes32 hash, bytes signature) external view returns (bytes4);
+    }
+}
+
+/// Parses a 65 bytes `r . s . v` signature, accepting both 0/1 and 27/28 for `v`
+pub fn parse_signature(value: &Value) -> Result<Signature> {
+    let bytes = match value {
+        Value::Bytes(bytes) => bytes,
+        _ => {
+            return Err(anyhow!(
+                "expected signature bytes, got {}",
+                value.get_type()
+            ))
+        }
+    };
+    Signature::try_from(bytes.as_slice()).map_err(|e| anyhow!("invalid signature: {}", e))
+}
+
+/// Builds a signature from its components, accepting both 0/1 and 27/28 for `v`
+fn signature_from_parts(v: U256, r: B256, s: B256) -> Result<Signature> {
+    let y_parity = match u64::try_from(v) {
+        Ok(0 | 27) => false,
+        Ok(1 | 28) => true,
+        _ => bail!("invalid signature v value: {}", v),
+    };
+    Ok(Signature::new(r.into(), s.into(), y_parity))
+}
+
+fn recover_address(hash: B256, signature: &Signature) -> Result<Address> {
+    signature
+        .recover_address_from_prehash(&hash)
+        .map_err(|e| anyhow!("failed to recover signer: {}", e))
+}
+
+fn ecrecover(_env: &Env, args: &[Value]) -> Result<Value> {
+    let (hash, v, r, s) = match args {
+        [hash, v, r, s] => (hash.as_b256()?, v.as_u256()?, r.as_b256()?, s.as_b256()?),
+        _ => bail!("ecrecover function expects hash, v, r and s as arguments"),
+    };
+    // mirror the precompile, which returns the zero address on failure
+    let addr = signature_from_parts(v, r, s)
+        .and_then(|signature| recover_address(hash, &signature))
+        .unwrap_or(Address::ZERO);
+    Ok(Value::Addr(addr))
+}
+
+fn recover(_env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    match args {
+        [hash, signature] => {
+            let signature = parse_signature(signature)?;
+            Ok(Value::Addr(recover_address(hash.as_b256()?, &signature)?))
+        }
+        _ => bail!("recover function expects a hash and a signature as arguments"),
+    }
+}
+
+/// Returns the `v`, `r` and `s` components of a signature, with `v` as 27/28
+fn signature_components(signature: &Signature) -> [(&'static str, Value); 3] {
+    [
+        ("v", Value::Uint(U256::from(27 + signature.v() as u8), 8)),
+        ("r", Value::FixBytes(B256::from(signature.r()), 32)),
+        ("s", Value::FixBytes(B256::from(signature.s()), 32)),
+    ]
+}
+
+fn split(_env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    match args {
+        [signature] => {
+            let components = signature_components(&parse_signature(signature)?);
+            Ok(Value::Tuple(
+                components.into_iter().map(|(_, value)| value).collect(),
+            ))
+        }
+        _ => bail!("split function expects a signature as argument"),
+    }
+}
+
+fn parse(_env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    match args {
+        [signature] => {
+            let fields = signature_components(&parse_signature(signature)?)
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect();
+            Ok(Value::NamedTuple(
+                "Signature".to_string(),
+                HashableIndexMap(fields),
+            ))
+        }
+        _ => bail!("parse function expects a signature as argument"),
+    }
+}
+
+fn join(_env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    match args {
+        [v, r, s] => {
+            let signature = signature_from_parts(v.as_u256()?, r.as_b256()?, s.as_b256()?)?;
+            Ok(Value::Bytes(signature.as_bytes().to_vec()))
+        }
+        _ => bail!("join function expects v, r and s as arguments"),
+    }
+}
+
+/// Checks a signature using EIP-1271 if the signer is a contract,
+/// and by recovering the signer otherwise
+async fn verify_signature(
+    env: &Env,
+    signer: Address,
+    hash: B256,
+    signature: &[u8],
+) -> Result<bool> {
+    let code = env
+        .get_provider()
+        .get_code_at(signer)
+        .block_id(env.block())
+        .await?;
+    if code.is_empty() {
+        let signature = parse_signature(&Value::Bytes(signature.to_vec()))?;
+        return Ok(recover_address(hash, &signature).ok() == Some(signer));
+    }
+    let contract = IERC1271::new(signer, env.get_provider());
+    let result = contract
+        .isValidSignature(hash, signature.to_vec().into())
+        .block(env.block())
+        .call()
+        .await;
+    match result {
+        Ok(value) => Ok(value == ERC1271_MAGIC_VALUE),
+        // only propagate errors unrelated to the execution of the call
+        Err(alloy::contract::Error::TransportError(e)) if e.as_error_resp().is_none() => {
+            Err(e.into())
+        }
+        Err(_) => Ok(false),
+    }
+}
+
+fn verify<'a>(
+    env: &'a mut Env,
+    _receiver: &'a Value,
+    args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        match args {
+            [Value::Addr(signer), Value::FixBytes(hash, 32), Value::Bytes(signature)] => {
+                let valid = verify_signature(env, *signer, *hash, signature).await?;
+                Ok(Value::Bool(valid))
+            }
+            _ => bail!("verify function expects a signer, a hash and a signature as arguments"),
+        }
+    }
+    .boxed()
+}
+
+lazy_static! {
+    pub static ref ECRECOVER: Arc<dyn FunctionDef> = SyncFunction::arc(
+        "ecrecover",
+        ecrecover,
+        vec![vec![
+            FunctionParam::new("hash", Type::FixBytes(32)),
+            FunctionParam::new("v", Type::Uint(256)),
+            FunctionParam::new("r", Type::FixBytes(32)),
+            FunctionParam::new("s", Type::FixBytes(32))
+        ]]
+    );
+    pub static ref SIG_RECOVER: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "recover",
+        recover,
+        vec![vec![
+            FunctionParam::new("hash", Type::FixBytes(32)),
+            FunctionParam::new("signature", Type::Bytes)
+        ]]
+    );
+    pub static ref SIG_SPLIT: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "split",
+        split,
+        vec![vec![FunctionParam::new("signature", Type::Bytes)]]
+    );
+    pub static ref SIG_PARSE: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "parse",
+        parse,
+        vec![vec![FunctionParam::new("signature", Type::Bytes)]]
+    );
+    pub static ref SIG_JOIN: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "join",
+        join,
+        vec![vec![
+            FunctionParam::new("v", Type::Uint(256)),
+            FunctionParam::new("r", Type::FixBytes(32)),
+            FunctionParam::new("s", Type::FixBytes(32))
+        ]]
+    );
+    pub static ref SIG_VERIFY: Arc<dyn FunctionDef> = AsyncMethod::arc(
+        "verify",
+        verify,
+        vec![vec![
+            FunctionParam::new("signer", Type::Address),
+            FunctionParam::new("hash", Type::FixBytes(32)),
+            FunctionParam::new("signature", Type::Bytes)
+        ]]
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_from_parts() {
+        assert!(signature_from_parts(U256::from(28), B256::ZERO, B256::ZERO)
+            .unwrap()
+            .v());
+        assert!(signature_from_parts(U256::from(2), B256::ZERO, B256::ZERO).is_err());
+        assert!(signature_from_parts(U256::MAX, B256::ZERO, B256::ZERO).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use alloy::primitives::{B256, U256};
+use anyhow::{anyhow, bail, Result};
+use lazy_static::lazy_static;
+
+use crate::interpreter::{
+    functions::{FunctionDef, FunctionParam, SyncMethod, SyncProperty},
+    storage::{self, ContractStorage},
+    ContractInfo, Env, Type, Value,
+};
+
+fn get_storage(env: &Env, receiver: &Value) -> Result<Value> {
+    let (ContractInfo(name, _), _, _) = receiver.as_contract()?;
+    let layout = env.get_storage_layout(&name).ok_or(anyhow!(
+        "no storage layout found for {}, make sure the compiler outputs storageLayout",
+        name
+    ))?;
+    Ok(ContractStorage::value(layout, receiver))
+}
+
+fn as_slot(value: &Value) -> Result<U256> {
+    match value {
+        Value::Uint(n, _) => Ok(*n),
+        Value::FixBytes(b, 32) => Ok(U256::from_be_bytes(b.0)),
+        _ => bail!("expected uint256 or bytes32, got {}", value.get_type()),
+    }
+}
+
+fn slot_value(slot: U256) -> Value {
+    Value::FixBytes(B256::from(slot.to_be_bytes::<32>()), 32)
+}
+
+fn mapping_slot(_env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    let (base, keys) = match args {
+        [base, keys @ ..] if !keys.is_empty() => (base, keys),
+        _ => bail!("mappingSlot function expects a slot and at least one key as arguments"),
+    };
+    let mut slot = as_slot(base)?;
+    for key in keys {
+        slot = storage::mapping_slot(key, slot)?;
+    }
+    Ok(slot_value(slot))
+}
+
+fn array_slot(_env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    let (base, index, element_size) = match args {
+        [base, index] => (base, index.as_usize()?, 32),
+        [base, index, element_size] => (base, index.as_usize()?, element_size.as_usize()?),
+        _ => bail!("arraySlot function expects a slot, an index and optionally an element size"),
+    };
+    if element_size == 0 {
+        bail!("element size must be greater than 0")
+    }
+    Ok(slot_value(storage::array_slot(
+        as_slot(base)?,
+        index,
+        element_size,
+    )))
+}
+
+fn erc7201_slot(_env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    match args {
+        [Value::Str(id)] => Ok(slot_value(storage::erc7201_slot(id))),
+        _ => bail!("erc7201 function expects a namespace id as argument"),
+    }
+}
+
+fn unpack(_env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
+    let (word, offset, size) = match args {
+        [word, offset, size] => (as_slot(word)?, offset.as_usize()?, size.as_usize()?),
+        _ => bail!("unpack function expects a word, an offset and a size as arguments"),
+    };
+    if size == 0 || offset + size > 32 {
+        bail!(
+            "cannot unpack {} bytes at offset {} of a 32 bytes word",
+            size,
+            offset
+        )
+    }
+    Ok(Value::Uint(storage::unpack(word, offset, size), size * 8))
+}
+
+lazy_static! {
+    pub static ref CONTRACT_STORAGE: Arc<dyn FunctionDef> =
+        SyncProperty::arc("storage", get_storage);
+    pub static ref STORAGE_MAPPING_SLOT: Arc<dyn FunctionDef> =
+        SyncMethod::arc("mappingSlot", mapping_slot, vec![]);
+    pub static ref STORAGE_ARRAY_SLOT: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "arraySlot",
+        array_slot,
+        vec![
+            vec![
+                FunctionParam::new("slot", Type::Any),
+                FunctionParam::new("index", Type::Uint(256))
+            ],
+            vec![
+                FunctionParam::new("slot", Type::Any),
+                FunctionParam::new("index", Type::Uint(256)),
+                FunctionParam::new("elementSize", Type::Uint(256))
+            ]
+        ]
+    );
+    pub static ref STORAGE_ERC7201: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "erc7201",
+        erc7201_slot,
+        vec![vec![FunctionParam::new("id", Type::String)]]
+    );
+    pub static ref STORAGE_UNPACK: Arc<dyn FunctionDef> = SyncMethod::arc(
+        "unpack",
+        unpack,
+        vec![vec![
+            FunctionParam::new("word", Type::Any),
+            FunctionParam::new("offset", Type::Uint(256)),
+            FunctionParam::new("size", Type::Uint(256))
+        ]]
+    );
+}
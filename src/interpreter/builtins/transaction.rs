@@ -2,16 +2,21 @@ use std::sync::Arc;
 
 use alloy::{
     consensus::{transaction::Recovered, EthereumTxEnvelope, Transaction, TxEip4844Variant},
-    providers::{PendingTransactionBuilder, Provider},
+    eips::{BlockId, Encodable2718, Typed2718},
+    network::TransactionBuilder,
+    primitives::{TxKind, U256},
+    providers::{PendingTransactionBuilder, Provider, SendableTx},
+    rpc::types::{TransactionInput, TransactionReceipt, TransactionRequest},
 };
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use futures::{future::BoxFuture, FutureExt};
 use lazy_static::lazy_static;
 
 use crate::interpreter::{
-    functions::{AsyncMethod, AsyncProperty, FunctionDef, FunctionParam},
-    utils::receipt_to_value,
-    Env, Type, Value,
+    functions::{AsyncMethod, AsyncProperty, CallOptions, FunctionDef, FunctionParam},
+    types::HashableIndexMap,
+    utils::{format_revert, receipt_to_value},
+    EclairProvider, Env, Type, Value,
 };
 
 fn wait_for_receipt<'a>(
@@ -20,11 +25,11 @@ fn wait_for_receipt<'a>(
     args: &'a [Value],
 ) -> BoxFuture<'a, Result<Value>> {
     async move {
-        let tx = match receiver {
-            Value::Transaction(tx) => *tx,
+        let (tx, chain) = match receiver {
+            Value::Transaction(tx, chain) => (*tx, chain),
             _ => bail!("wait_for_receipt function expects a transaction as argument"),
         };
-        let provider = env.get_provider();
+        let provider = env.get_chain_provider(chain.as_deref()).await?;
         let tx = PendingTransactionBuilder::new(provider.root().clone(), tx);
         if args.len() > 1 {
             bail!("get_receipt function expects at most one argument")
@@ -35,20 +40,61 @@ fn wait_for_receipt<'a>(
             .with_timeout(Some(std::time::Duration::from_secs(timeout)))
             .get_receipt()
             .await?;
-        receipt_to_value(env, receipt)
+        let revert_reason = if receipt.status() {
+            None
+        } else {
+            get_revert_reason(env, &provider, &receipt).await.ok()
+        };
+        let mut value = receipt_to_value(env, receipt)?;
+        if let Value::NamedTuple(_, fields) = &mut value {
+            fields.0.insert(
+                "revertReason".to_string(),
+                revert_reason.map(Value::Str).unwrap_or(Value::Null),
+            );
+        }
+        Ok(value)
     }
     .boxed()
 }
 
+// Replays a failed transaction as a call on top of its parent block to recover the revert data
+async fn get_revert_reason(
+    env: &mut Env,
+    provider: &EclairProvider,
+    receipt: &TransactionReceipt,
+) -> Result<String> {
+    let tx = provider
+        .get_transaction_by_hash(receipt.transaction_hash)
+        .await?
+        .ok_or(anyhow!("transaction not found"))?;
+    let block_number = receipt
+        .block_number
+        .ok_or(anyhow!("transaction not mined"))?;
+    let tx_req = TransactionRequest::from(tx);
+    let err = match provider
+        .call(tx_req)
+        .block(BlockId::number(block_number.saturating_sub(1)))
+        .await
+    {
+        Ok(_) => bail!("transaction did not revert when replayed"),
+        Err(err) => err,
+    };
+    let revert_data = err
+        .as_error_resp()
+        .and_then(|e| e.as_revert_data())
+        .ok_or(anyhow!("no revert data"))?;
+    Ok(format_revert(env, &revert_data).await)
+}
+
 async fn get_tx(
     env: &Env,
     value: &Value,
 ) -> Result<Recovered<EthereumTxEnvelope<TxEip4844Variant>>> {
-    let local_tx = match value {
-        Value::Transaction(tx) => *tx,
+    let (local_tx, chain) = match value {
+        Value::Transaction(tx, chain) => (*tx, chain),
         _ => bail!("expected a transaction as argument"),
     };
-    let provider = env.get_provider();
+    let provider = env.get_connected_chain_provider(chain.as_deref())?;
     let tx = provider
         .get_transaction_by_hash(local_tx)
         .await?
@@ -85,6 +131,211 @@ fn get_to<'a>(env: &'a Env, receiver: &'a Value) -> BoxFuture<'a, Result<Value>>
     .boxed()
 }
 
+/// Fee multiplier used by default when replacing a transaction, in basis points
+const DEFAULT_FEE_BUMP: u128 = 12_000;
+
+/// Minimum fee multiplier accepted by nodes to replace a pending transaction, in basis points
+const MIN_FEE_BUMP: u128 = 11_000;
+
+/// Re-sends a pending transaction with the same nonce and fees multiplied by `bump`
+/// basis points, replacing it with a 0-value self-send when `cancel` is set
+async fn replace_transaction(
+    env: &mut Env,
+    receiver: &Value,
+    bump: u128,
+    cancel: bool,
+) -> Result<Value> {
+    let (hash, chain) = match receiver {
+        Value::Transaction(tx, chain) => (*tx, chain.clone()),
+        _ => bail!("expected a transaction as receiver"),
+    };
+    if bump < MIN_FEE_BUMP {
+        bail!("fees must be bumped by at least 10% to replace a transaction");
+    }
+    let provider = env.get_connected_chain_provider(chain.as_deref())?;
+    let tx = provider
+        .get_transaction_by_hash(hash)
+        .await?
+        .ok_or(anyhow!("Transaction not found"))?;
+    if tx.block_number.is_some() {
+        bail!("transaction {} is already mined", hash);
+    }
+    if tx.is_eip4844() {
+        bail!("blob transactions cannot be replaced");
+    }
+    let from = tx.inner.signer();
+    if env.get_default_sender() != Some(from) {
+        bail!(
+            "transaction was sent from {}, select this account to replace it",
+            from
+        );
+    }
+
+    let mut tx_req = if cancel {
+        TransactionRequest::default()
+            .with_to(from)
+            .value(U256::ZERO)
+    } else {
+        let mut tx_req = TransactionRequest::default()
+            .input(TransactionInput::new(tx.input().clone()))
+            .value(tx.value())
+            .with_gas_limit(tx.gas_limit());
+        tx_req.to = Some(tx.kind());
+        tx_req.access_list = tx.access_list().cloned();
+        tx_req.authorization_list = tx.authorization_list().map(|list| list.to_vec());
+        tx_req
+    };
+    tx_req = tx_req.with_from(from).with_nonce(tx.nonce());
+    // nodes require every fee to increase, so fees are also raised above
+    // their previous value and to the current estimate
+    let bump_fee = |fee: u128, estimate: u128| {
+        let bumped = fee
+            .checked_mul(bump)
+            .map(|fee| fee / 10_000)
+            .ok_or(anyhow!("fee bump overflows"))?;
+        Ok::<_, anyhow::Error>(bumped.max(fee.saturating_add(1)).max(estimate))
+    };
+    if tx.is_dynamic_fee() {
+        let estimate = provider.estimate_eip1559_fees().await?;
+        tx_req = tx_req
+            .with_max_fee_per_gas(bump_fee(tx.max_fee_per_gas(), estimate.max_fee_per_gas)?)
+            .with_max_priority_fee_per_gas(bump_fee(
+                tx.max_priority_fee_per_gas().unwrap_or_default(),
+                estimate.max_priority_fee_per_gas,
+            )?);
+    } else {
+        let estimate = provider.get_gas_price().await?;
+        tx_req = tx_req.with_gas_price(bump_fee(tx.gas_price().unwrap_or_default(), estimate)?);
+    }
+    env.get_fee_config().check_cap(&tx_req)?;
+
+    let pending = provider.send_transaction(tx_req).await?;
+    env.untrack_transactions(&[hash]);
+    env.track_transaction(*pending.tx_hash(), chain.clone());
+    Ok(Value::Transaction(*pending.tx_hash(), chain))
+}
+
+fn speed_up<'a>(
+    env: &'a mut Env,
+    receiver: &'a Value,
+    args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        let bump = match args {
+            [] => DEFAULT_FEE_BUMP,
+            [multiplier] => {
+                let (digits, decimals) = multiplier.as_decimal()?;
+                let bump = digits
+                    .checked_mul(U256::from(10_000))
+                    .map(|bump| bump / U256::from(10).pow(U256::from(decimals)))
+                    .ok_or(anyhow!("fee multiplier is too large"))?;
+                u128::try_from(bump)?
+            }
+            _ => bail!("speedUp function expects an optional fee multiplier as argument"),
+        };
+        replace_transaction(env, receiver, bump, false).await
+    }
+    .boxed()
+}
+
+fn cancel<'a>(
+    env: &'a mut Env,
+    receiver: &'a Value,
+    _args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    replace_transaction(env, receiver, DEFAULT_FEE_BUMP, true).boxed()
+}
+
+/// Sends or signs a transaction with arbitrary calldata,
+/// using `null` as the target to deploy a contract
+#[derive(Debug)]
+struct SendTransaction {
+    name: &'static str,
+    broadcast: bool,
+}
+
+impl FunctionDef for SendTransaction {
+    fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    fn get_valid_args(&self, _receiver: &Option<Value>) -> Vec<Vec<FunctionParam>> {
+        let data = FunctionParam::new("data", Type::Bytes);
+        vec![
+            vec![FunctionParam::new("to", Type::Address), data.clone()],
+            vec![FunctionParam::new("to", Type::Null), data],
+        ]
+    }
+
+    fn is_property(&self) -> bool {
+        false
+    }
+
+    fn execute<'a>(
+        &'a self,
+        env: &'a mut Env,
+        values: &'a [Value],
+        options: &'a HashableIndexMap<String, Value>,
+    ) -> BoxFuture<'a, Result<Value>> {
+        async move {
+            let opts: CallOptions = options.try_into()?;
+            opts.validate_send()?;
+            let (to, data) = match &values[1..] {
+                [Value::Addr(to), Value::Bytes(data)] => (TxKind::Call(*to), data),
+                [Value::Null, Value::Bytes(data)] => (TxKind::Create, data),
+                _ => bail!(
+                    "{} function expects a target and calldata as arguments",
+                    self.name
+                ),
+            };
+            let from = env
+                .get_default_sender()
+                .ok_or(anyhow!("no wallet connected"))?;
+            let provider = env.get_provider();
+            let input = TransactionInput::new(data.clone().into());
+            let mut tx_req = TransactionRequest::default().with_from(from).input(input);
+            tx_req.to = Some(to);
+            if let Some(value) = opts.value() {
+                tx_req = tx_req.with_value(value);
+            }
+            let mut tx_req = opts.apply_to_send(env, &provider, tx_req).await?;
+
+            if self.broadcast {
+                let tx = provider.send_transaction(tx_req).await?;
+                env.track_transaction(*tx.tx_hash(), None);
+                return Ok(Value::Transaction(*tx.tx_hash(), None));
+            }
+            // the nonce filler would reserve the nonce for a transaction that may never be sent
+            if tx_req.nonce.is_none() {
+                let nonce = provider.get_transaction_count(from).pending().await?;
+                tx_req = tx_req.with_nonce(nonce);
+            }
+            match provider.fill(tx_req).await? {
+                SendableTx::Envelope(envelope) => Ok(Value::Bytes(envelope.encoded_2718())),
+                SendableTx::Builder(_) => bail!("no signer loaded for {}", from),
+            }
+        }
+        .boxed()
+    }
+}
+
+fn send_raw_transaction<'a>(
+    env: &'a mut Env,
+    _receiver: &'a Value,
+    args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        let raw = match args {
+            [Value::Bytes(raw)] => raw,
+            _ => bail!("sendRaw function expects signed transaction bytes as argument"),
+        };
+        let tx = env.get_provider().send_raw_transaction(raw).await?;
+        env.track_transaction(*tx.tx_hash(), None);
+        Ok(Value::Transaction(*tx.tx_hash(), None))
+    }
+    .boxed()
+}
+
 lazy_static! {
     pub static ref TX_GET_RECEIPT: Arc<dyn FunctionDef> = AsyncMethod::arc(
         "getReceipt",
@@ -95,4 +346,27 @@ lazy_static! {
         AsyncProperty::arc("input", get_input_data);
     pub static ref TX_GET_FROM: Arc<dyn FunctionDef> = AsyncProperty::arc("from", get_from);
     pub static ref TX_GET_TO: Arc<dyn FunctionDef> = AsyncProperty::arc("to", get_to);
+    pub static ref TX_SPEED_UP: Arc<dyn FunctionDef> = AsyncMethod::arc(
+        "speedUp",
+        speed_up,
+        vec![
+            vec![],
+            vec![FunctionParam::new("multiplier", Type::Decimal)]
+        ]
+    );
+    pub static ref TX_CANCEL: Arc<dyn FunctionDef> =
+        AsyncMethod::arc("cancel", cancel, vec![vec![]]);
+    pub static ref TX_SEND: Arc<dyn FunctionDef> = Arc::new(SendTransaction {
+        name: "send",
+        broadcast: true,
+    });
+    pub static ref TX_SIGN: Arc<dyn FunctionDef> = Arc::new(SendTransaction {
+        name: "sign",
+        broadcast: false,
+    });
+    pub static ref TX_SEND_RAW: Arc<dyn FunctionDef> = AsyncMethod::arc(
+        "sendRaw",
+        send_raw_transaction,
+        vec![vec![FunctionParam::new("raw", Type::Bytes)]]
+    );
 }
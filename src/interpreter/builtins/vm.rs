@@ -2,6 +2,8 @@ use std::sync::Arc;
 
 use crate::interpreter::{
     functions::{AsyncMethod, AsyncProperty, FunctionDef, FunctionParam, SyncMethod},
+    proxy,
+    types::HashableIndexMap,
     Env, Type, Value,
 };
 use alloy::{
@@ -10,6 +12,7 @@ use alloy::{
 };
 use anyhow::{bail, Result};
 use futures::{future::BoxFuture, FutureExt};
+use indexmap::IndexMap;
 use lazy_static::lazy_static;
 
 fn impersonate<'a>(
@@ -40,15 +43,22 @@ fn stop_impersonate<'a>(
     .boxed()
 }
 
-fn rpc(env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
-    match args {
-        [] => Ok(Value::Str(env.get_rpc_url())),
-        [url] => {
-            env.set_provider_url(&url.as_string()?)?;
-            Ok(Value::Null)
+fn rpc<'a>(
+    env: &'a mut Env,
+    _receiver: &'a Value,
+    args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        match args {
+            [] => Ok(Value::Str(env.get_rpc_url())),
+            [url] => {
+                env.set_provider_url(&url.as_string()?).await?;
+                Ok(Value::Null)
+            }
+            _ => bail!("rpc: invalid arguments"),
         }
-        _ => bail!("rpc: invalid arguments"),
     }
+    .boxed()
 }
 
 fn fork<'a>(
@@ -127,6 +137,53 @@ fn mine<'a>(
     .boxed()
 }
 
+fn get_implementation<'a>(
+    env: &'a mut Env,
+    _receiver: &'a Value,
+    args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        let address = match args {
+            [Value::Addr(address)] => *address,
+            _ => bail!("implementation: invalid arguments"),
+        };
+        let proxy = proxy::get_proxy(env, address).await?;
+        Ok(proxy.map_or(Value::Null, |p| Value::Addr(p.implementation)))
+    }
+    .boxed()
+}
+
+fn get_proxy<'a>(
+    env: &'a mut Env,
+    _receiver: &'a Value,
+    args: &'a [Value],
+) -> BoxFuture<'a, Result<Value>> {
+    async move {
+        let address = match args {
+            [Value::Addr(address)] => *address,
+            _ => bail!("proxy: invalid arguments"),
+        };
+        let proxy = match proxy::get_proxy(env, address).await? {
+            Some(proxy) => proxy,
+            None => return Ok(Value::Null),
+        };
+        let optional_address = |addr: Option<_>| addr.map_or(Value::Null, Value::Addr);
+        let mut fields = IndexMap::new();
+        fields.insert("kind".to_string(), Value::Str(proxy.kind.to_string()));
+        fields.insert(
+            "implementation".to_string(),
+            Value::Addr(proxy.implementation),
+        );
+        fields.insert("beacon".to_string(), optional_address(proxy.beacon));
+        fields.insert("admin".to_string(), optional_address(proxy.admin));
+        Ok(Value::NamedTuple(
+            "Proxy".to_string(),
+            HashableIndexMap(fields),
+        ))
+    }
+    .boxed()
+}
+
 fn get_env_var(_env: &mut Env, _receiver: &Value, args: &[Value]) -> Result<Value> {
     let key = match args {
         [Value::Str(key)] => key.clone(),
@@ -154,7 +211,7 @@ lazy_static! {
     );
     pub static ref VM_STOP_PRANK: Arc<dyn FunctionDef> =
         AsyncMethod::arc("stopPrank", stop_impersonate, vec![vec![]]);
-    pub static ref VM_RPC: Arc<dyn FunctionDef> = SyncMethod::arc(
+    pub static ref VM_RPC: Arc<dyn FunctionDef> = AsyncMethod::arc(
         "rpc",
         rpc,
         vec![vec![], vec![FunctionParam::new("url", Type::String)]]
@@ -194,6 +251,16 @@ lazy_static! {
     );
     pub static ref VM_IS_CONNECTED: Arc<dyn FunctionDef> =
         AsyncProperty::arc("connected", is_connected);
+    pub static ref VM_IMPLEMENTATION: Arc<dyn FunctionDef> = AsyncMethod::arc(
+        "implementation",
+        get_implementation,
+        vec![vec![FunctionParam::new("proxy", Type::Address)]]
+    );
+    pub static ref VM_PROXY: Arc<dyn FunctionDef> = AsyncMethod::arc(
+        "proxy",
+        get_proxy,
+        vec![vec![FunctionParam::new("proxy", Type::Address)]]
+    );
     pub static ref VM_ENV: Arc<dyn FunctionDef> = SyncMethod::arc(
         "getEnv",
         get_env_var,
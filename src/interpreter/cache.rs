@@ -0,0 +1,406 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use alloy::{
+    hex,
+    primitives::{keccak256, U64},
+    rpc::json_rpc::{
+        Id, Request, RequestPacket, Response, ResponsePacket, ResponsePayload, RpcSend,
+        SerializedRequest,
+    },
+    transports::{TransportError, TransportErrorKind, TransportFut},
+};
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde_json::{value::RawValue, Value as JsonValue};
+use tokio::sync::OnceCell;
+use tower::{Layer, Service};
+
+const CACHE_DIR: &str = "cache/eclair/rpc";
+
+/// Number of blocks behind the head after which a block is considered final
+/// on chains that do not support the `finalized` block tag
+const FALLBACK_FINALITY_DEPTH: u64 = 64;
+
+/// Methods whose result only depends on their parameters once a block is fixed,
+/// with the position of the block parameter
+const CACHEABLE_METHODS: [(&str, usize); 5] = [
+    ("eth_call", 1),
+    ("eth_getBalance", 1),
+    ("eth_getCode", 1),
+    ("eth_getStorageAt", 2),
+    ("eth_getLogs", 0),
+];
+
+/// On-disk cache of RPC responses, stored as one file per request
+/// under a directory per chain
+#[derive(Debug, Clone)]
+pub struct RpcCache {
+    dir: PathBuf,
+}
+
+impl Default for RpcCache {
+    fn default() -> Self {
+        let base_dir = foundry_config::Config::foundry_dir().unwrap_or_else(std::env::temp_dir);
+        RpcCache::new(base_dir.join(CACHE_DIR))
+    }
+}
+
+impl RpcCache {
+    pub fn new(dir: PathBuf) -> Self {
+        RpcCache { dir }
+    }
+
+    fn entry_path(&self, chain_id: u64, method: &str, params: &str) -> PathBuf {
+        let key = keccak256(format!("{}{}", method, params));
+        self.dir
+            .join(chain_id.to_string())
+            .join(format!("{}.json", hex::encode(key)))
+    }
+
+    fn get(&self, chain_id: u64, method: &str, params: &str) -> Option<Box<RawValue>> {
+        let content = fs::read_to_string(self.entry_path(chain_id, method, params)).ok()?;
+        RawValue::from_string(content).ok()
+    }
+
+    fn put(&self, chain_id: u64, method: &str, params: &str, result: &RawValue) -> Result<()> {
+        let path = self.entry_path(chain_id, method, params);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, result.get())?;
+        Ok(())
+    }
+
+    /// Removes all the cached responses
+    pub fn clear(&self) -> Result<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of cached responses and their total size in bytes
+    pub fn stats(&self) -> Result<(u64, u64)> {
+        let (mut entries, mut size) = (0, 0);
+        if !self.dir.exists() {
+            return Ok((entries, size));
+        }
+        for chain_dir in fs::read_dir(&self.dir)? {
+            for entry in fs::read_dir(chain_dir?.path())? {
+                entries += 1;
+                size += entry?.metadata()?.len();
+            }
+        }
+        Ok((entries, size))
+    }
+}
+
+/// Block targeted by a request that does not depend on the chain head
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FixedBlock {
+    Hash,
+    Number(u64),
+}
+
+/// Returns the block referred to by the block parameter,
+/// or `None` for a tag such as `latest` or `pending`
+fn fixed_block(block: &JsonValue) -> Option<FixedBlock> {
+    let number = match block {
+        JsonValue::String(s) => s.as_str(),
+        JsonValue::Object(obj) if obj.contains_key("blockHash") => return Some(FixedBlock::Hash),
+        JsonValue::Object(obj) => obj.get("blockNumber")?.as_str()?,
+        _ => return None,
+    };
+    let number = u64::from_str_radix(number.strip_prefix("0x")?, 16).ok()?;
+    Some(FixedBlock::Number(number))
+}
+
+/// Returns the block a cacheable request reads from, which is the highest one
+/// for logs queried over a range of blocks
+fn request_block(method: &str, params: &JsonValue) -> Option<FixedBlock> {
+    let (_, block_index) = CACHEABLE_METHODS.iter().find(|(m, _)| *m == method)?;
+    let param = params.get(block_index)?;
+    if method != "eth_getLogs" {
+        return fixed_block(param);
+    }
+    if param.get("blockHash").is_some() {
+        return Some(FixedBlock::Hash);
+    }
+    match (
+        fixed_block(param.get("fromBlock")?)?,
+        fixed_block(param.get("toBlock")?)?,
+    ) {
+        (FixedBlock::Number(from), FixedBlock::Number(to)) => {
+            Some(FixedBlock::Number(from.max(to)))
+        }
+        _ => None,
+    }
+}
+
+/// Layer serving historical reads from the on-disk cache,
+/// forwarding all the requests when no cache is given
+#[derive(Debug, Clone)]
+pub struct CacheLayer {
+    cache: Option<Arc<RpcCache>>,
+}
+
+impl CacheLayer {
+    pub fn new(cache: Option<Arc<RpcCache>>) -> Self {
+        CacheLayer { cache }
+    }
+}
+
+impl<S> Layer<S> for CacheLayer {
+    type Service = CacheService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CacheService {
+            inner,
+            cache: self.cache.clone(),
+            chain_id: Arc::new(OnceCell::new()),
+            finalized_block: Arc::new(AtomicU64::new(0)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheService<S> {
+    inner: S,
+    cache: Option<Arc<RpcCache>>,
+    chain_id: Arc<OnceCell<u64>>,
+    /// Highest block known to be finalized, below which responses can be cached
+    finalized_block: Arc<AtomicU64>,
+}
+
+async fn fetch<S, P, R>(inner: &mut S, method: &'static str, params: P) -> Result<R, TransportError>
+where
+    S: Service<RequestPacket, Response = ResponsePacket, Error = TransportError>,
+    P: RpcSend,
+    R: DeserializeOwned,
+{
+    let request: SerializedRequest = Request::new(method, Id::Number(0), params)
+        .serialize()
+        .map_err(TransportError::ser_err)?;
+    let response = inner.call(RequestPacket::Single(request)).await?;
+    match response {
+        ResponsePacket::Single(Response {
+            payload: ResponsePayload::Success(data),
+            ..
+        }) => {
+            serde_json::from_str(data.get()).map_err(|e| TransportError::deser_err(e, data.get()))
+        }
+        _ => Err(TransportErrorKind::custom_str(&format!(
+            "failed to fetch {}",
+            method
+        ))),
+    }
+}
+
+async fn fetch_chain_id<S>(inner: &mut S) -> Result<u64, TransportError>
+where
+    S: Service<RequestPacket, Response = ResponsePacket, Error = TransportError>,
+{
+    let chain_id: U64 = fetch(inner, "eth_chainId", ()).await?;
+    Ok(chain_id.to())
+}
+
+/// Returns the latest finalized block, or the block `FALLBACK_FINALITY_DEPTH`
+/// blocks behind the head on chains without the `finalized` tag
+async fn fetch_finalized_block<S>(inner: &mut S) -> Result<u64, TransportError>
+where
+    S: Service<RequestPacket, Response = ResponsePacket, Error = TransportError>,
+{
+    let block: Result<JsonValue, _> =
+        fetch(inner, "eth_getBlockByNumber", ("finalized", false)).await;
+    let number = block
+        .ok()
+        .and_then(|block| serde_json::from_value::<U64>(block.get("number")?.clone()).ok());
+    if let Some(number) = number {
+        return Ok(number.to());
+    }
+    let head: U64 = fetch(inner, "eth_blockNumber", ()).await?;
+    Ok(head.to::<u64>().saturating_sub(FALLBACK_FINALITY_DEPTH))
+}
+
+impl<S> Service<RequestPacket> for CacheService<S>
+where
+    S: Service<RequestPacket, Response = ResponsePacket, Error = TransportError>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let mut inner = self.inner.clone();
+        let cache = self.cache.clone();
+        let chain_id = self.chain_id.clone();
+        let finalized_block = self.finalized_block.clone();
+
+        Box::pin(async move {
+            let (cache, req) = match (cache, &request) {
+                (Some(cache), RequestPacket::Single(req)) => (cache, req),
+                _ => return inner.call(request).await,
+            };
+            let method = req.method().to_string();
+            let params = req
+                .params()
+                .map(|p| p.get().to_string())
+                .unwrap_or_default();
+            let parsed_params = serde_json::from_str(&params).unwrap_or(JsonValue::Null);
+            let block = match request_block(&method, &parsed_params) {
+                Some(block) => block,
+                None => return inner.call(request).await,
+            };
+            // blocks that are not finalized yet can still be reorganized
+            if let FixedBlock::Number(number) = block {
+                if number > finalized_block.load(Ordering::Relaxed) {
+                    let latest = fetch_finalized_block(&mut inner).await.unwrap_or_default();
+                    if number
+                        > finalized_block
+                            .fetch_max(latest, Ordering::Relaxed)
+                            .max(latest)
+                    {
+                        return inner.call(request).await;
+                    }
+                }
+            }
+
+            let id = req.id().clone();
+            let chain_id = *chain_id
+                .get_or_try_init(|| fetch_chain_id(&mut inner))
+                .await?;
+            if let Some(result) = cache.get(chain_id, &method, &params) {
+                return Ok(ResponsePacket::Single(Response {
+                    id,
+                    payload: ResponsePayload::Success(result),
+                }));
+            }
+
+            let response = inner.call(request).await?;
+            if let ResponsePacket::Single(Response {
+                payload: ResponsePayload::Success(result),
+                ..
+            }) = &response
+            {
+                if let Err(e) = cache.put(chain_id, &method, &params, result) {
+                    eprintln!("warning: failed to cache {} response: {}", method, e);
+                }
+            }
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::transports::mock::{Asserter, MockTransport};
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_request_block() {
+        let tx = json!({"to": "0x0000000000000000000000000000000000000001"});
+        assert_eq!(
+            request_block("eth_call", &json!([tx, "0x10"])),
+            Some(FixedBlock::Number(16))
+        );
+        assert_eq!(request_block("eth_call", &json!([tx, "latest"])), None);
+        assert_eq!(request_block("eth_call", &json!([tx])), None);
+        assert_eq!(
+            request_block("eth_call", &json!([tx, {"blockHash": "0x01"}])),
+            Some(FixedBlock::Hash)
+        );
+        assert_eq!(
+            request_block(
+                "eth_getStorageAt",
+                &json!(["0x0000000000000000000000000000000000000001", "0x0", "0x10"])
+            ),
+            Some(FixedBlock::Number(16))
+        );
+        assert_eq!(
+            request_block(
+                "eth_getBalance",
+                &json!(["0x0000000000000000000000000000000000000001", "pending"])
+            ),
+            None
+        );
+        assert_eq!(
+            request_block(
+                "eth_getLogs",
+                &json!([{"fromBlock": "0x1", "toBlock": "0x10"}])
+            ),
+            Some(FixedBlock::Number(16))
+        );
+        assert_eq!(
+            request_block(
+                "eth_getLogs",
+                &json!([{"fromBlock": "0x1", "toBlock": "latest"}])
+            ),
+            None
+        );
+        assert_eq!(request_block("eth_blockNumber", &json!([])), None);
+    }
+
+    #[tokio::test]
+    async fn test_only_cache_finalized_blocks() {
+        let dir = std::env::temp_dir().join(format!("eclair-cache-{}", std::process::id()));
+        let cache = Arc::new(RpcCache::new(dir.clone()));
+        let asserter = Asserter::new();
+        let mut service =
+            CacheLayer::new(Some(cache.clone())).layer(MockTransport::new(asserter.clone()));
+        let balance = |block: &str| {
+            let request = Request::new(
+                "eth_getBalance",
+                Id::Number(1),
+                (
+                    "0x0000000000000000000000000000000000000001",
+                    block.to_string(),
+                ),
+            );
+            RequestPacket::Single(request.serialize().unwrap())
+        };
+
+        asserter.push_success(&"0x1"); // chain id
+        asserter.push_success(&json!({"number": "0x20"})); // finalized block
+        asserter.push_success(&"0x5");
+        service.call(balance("0x10")).await.unwrap();
+        // served from the cache without querying the finalized block again
+        service.call(balance("0x10")).await.unwrap();
+
+        // not finalized yet, so always forwarded
+        asserter.push_success(&json!({"number": "0x20"}));
+        asserter.push_success(&"0x6");
+        service.call(balance("0x30")).await.unwrap();
+        asserter.push_success(&json!({"number": "0x20"}));
+        asserter.push_success(&"0x6");
+        service.call(balance("0x30")).await.unwrap();
+
+        // falls back to a fixed depth behind the head without a finalized tag
+        asserter.push_failure_msg("unknown block tag");
+        asserter.push_success(&"0x100");
+        asserter.push_success(&"0x7");
+        service.call(balance("0x80")).await.unwrap();
+        service.call(balance("0x80")).await.unwrap();
+
+        assert!(asserter.read_q().is_empty());
+        assert_eq!(cache.stats().unwrap().0, 2);
+        cache.clear().unwrap();
+    }
+}
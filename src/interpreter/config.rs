@@ -1,11 +1,17 @@
-use std::collections::{BTreeMap, HashMap};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
 
 use foundry_config::Chain;
 
 use crate::loaders::EtherscanConfig;
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 
 const DEFAULT_RPC_URL: &str = "http://localhost:8545";
+const DEFAULT_MAX_RETRIES: u32 = 5;
+const DEFAULT_INITIAL_BACKOFF: u64 = 500;
+const DEFAULT_COMPUTE_UNITS_PER_SECOND: u64 = 330;
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -13,6 +19,22 @@ pub struct Config {
     pub debug: bool,
     pub rpc_endpoints: BTreeMap<String, String>,
     pub etherscan: HashMap<Chain, EtherscanConfig>,
+    /// Log every JSON-RPC request, even when debug mode is off
+    pub log_rpc: bool,
+    /// Maximum number of retries on rate limits and transient RPC errors
+    pub max_retries: u32,
+    /// Initial backoff in milliseconds before retrying a request
+    pub initial_backoff: u64,
+    /// Compute units per second allowed by the RPC provider, used to compute backoffs
+    pub compute_units_per_second: u64,
+    /// Maximum number of requests sent per second, unlimited if not set
+    pub requests_per_second: Option<u32>,
+    /// Cache responses of RPC reads at a fixed block on disk
+    pub rpc_cache: bool,
+    /// File to record the RPC requests and their responses to
+    pub record: Option<PathBuf>,
+    /// File of recorded RPC calls to serve requests from instead of the network
+    pub replay: Option<PathBuf>,
 }
 
 impl Config {
@@ -38,9 +60,32 @@ impl Config {
             debug,
             rpc_endpoints,
             etherscan,
+            log_rpc: false,
+            max_retries: DEFAULT_MAX_RETRIES,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            compute_units_per_second: DEFAULT_COMPUTE_UNITS_PER_SECOND,
+            requests_per_second: None,
+            rpc_cache: false,
+            record: None,
+            replay: None,
         }
     }
 
+    /// Resolves an RPC URL, an alias from `rpc_endpoints` or an IPC path
+    pub fn resolve_rpc_url(&self, url: &str) -> Result<String> {
+        if url::Url::parse(url).is_ok() {
+            return Ok(url.to_string());
+        }
+        if let Some(endpoint) = self.rpc_endpoints.get(url) {
+            return Ok(endpoint.clone());
+        }
+        let path = shellexpand::tilde(url);
+        if std::path::Path::new(path.as_ref()).exists() {
+            return Ok(path.to_string());
+        }
+        bail!("invalid URL and no config for {}", url)
+    }
+
     pub fn get_etherscan_config(&self, chain_id: u64) -> Result<EtherscanConfig> {
         self.etherscan
             .get(&Chain::from_id(chain_id))
@@ -0,0 +1,312 @@
+use alloy::{
+    hex,
+    network::TransactionBuilder,
+    primitives::{address, keccak256, Address, Bytes, B256},
+    providers::Provider,
+    rpc::{json_rpc::RpcError, types::TransactionRequest},
+    sol,
+    sol_types::{SolCall, SolError, SolValue},
+    transports::{http::reqwest, TransportErrorKind},
+};
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{functions::FunctionParam, Env, Type, Value};
+
+/// ENS registry, deployed at the same address on mainnet and testnets
+const ENS_REGISTRY: Address = address!("00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
+
+/// ENS UniversalResolver, deployed at the same address on mainnet and testnets,
+/// which finds the resolver of a name, including wildcard resolvers (ENSIP-10)
+const UNIVERSAL_RESOLVER: Address = address!("eEeEEEeE14D718C2B47D9923Deab1335E144EeEe");
+
+/// Maximum number of offchain lookups followed for a single call, as recommended by EIP-3668
+const MAX_OFFCHAIN_LOOKUPS: usize = 4;
+
+sol! {
+    #[sol(rpc)]
+    interface EnsRegistry {
+        function resolver(bytes32 node) external view returns (address);
+    }
+
+    #[sol(rpc)]
+    interface EnsResolver {
+        function addr(bytes32 node) external view returns (address);
+        function name(bytes32 node) external view returns (string);
+    }
+
+    interface UniversalResolver {
+        error ResolverNotFound(bytes name);
+
+        function resolve(bytes name, bytes data) external view returns (bytes result, address resolver);
+    }
+
+    /// Revert asking the caller to fetch data offchain, as defined in EIP-3668
+    error OffchainLookup(address sender, string[] urls, bytes callData, bytes4 callbackFunction, bytes extraData);
+}
+
+/// Normalizes a name as defined in ENSIP-15: labels are mapped with UTS-46,
+/// which lowercases them, applies the NFC normalization and drops ignored characters
+/// such as variation selectors, and are then checked against the ENSIP-15 label rules
+pub fn normalize(name: &str) -> Result<String> {
+    let (normalized, result) = idna::domain_to_unicode(name);
+    if result.is_err() {
+        bail!("invalid ENS name {}", name);
+    }
+    // punycode labels are decoded by UTS-46 but are not valid ENS labels
+    for label in name.to_lowercase().split('.').chain(normalized.split('.')) {
+        if label.is_empty() {
+            bail!("invalid ENS name {}: empty label", name);
+        }
+        if label.trim_start_matches('_').contains('_') {
+            bail!(
+                "invalid ENS name {}: underscores are only allowed at the start of a label",
+                name
+            );
+        }
+        if label.is_ascii() && label.get(2..4) == Some("--") {
+            bail!(
+                "invalid ENS name {}: labels cannot have hyphens as third and fourth characters",
+                name
+            );
+        }
+    }
+    Ok(normalized)
+}
+
+/// Computes the ENS node of a name, as defined in EIP-137, after normalizing it
+pub fn namehash(name: &str) -> Result<B256> {
+    let mut node = B256::ZERO;
+    if name.is_empty() {
+        return Ok(node);
+    }
+    for label in normalize(name)?.rsplit('.') {
+        let label_hash = keccak256(label.as_bytes());
+        node = keccak256([node.as_slice(), label_hash.as_slice()].concat());
+    }
+    Ok(node)
+}
+
+/// Encodes a name in the DNS wire format expected by the UniversalResolver,
+/// replacing labels too long to be encoded by their hash, as defined in ENSIP-10
+fn dns_encode(name: &str) -> Vec<u8> {
+    let mut encoded = vec![];
+    for label in name.split('.').filter(|label| !label.is_empty()) {
+        let label = match label.len() {
+            0..=255 => label.to_string(),
+            _ => format!("[{}]", hex::encode(keccak256(label))),
+        };
+        encoded.push(label.len() as u8);
+        encoded.extend_from_slice(label.as_bytes());
+    }
+    encoded.push(0);
+    encoded
+}
+
+/// Returns true for strings that are implicitly resolved when an address is expected
+pub fn is_ens_name(name: &str) -> bool {
+    name.len() > ".eth".len() && name.ends_with(".eth") && !name.contains(char::is_whitespace)
+}
+
+async fn get_resolver(env: &Env, node: B256) -> Result<Option<Address>> {
+    let registry = EnsRegistry::new(ENS_REGISTRY, env.get_provider());
+    let resolver = registry.resolver(node).block(env.block()).call().await?;
+    Ok((!resolver.is_zero()).then_some(resolver))
+}
+
+#[derive(Deserialize)]
+struct GatewayResponse {
+    data: Bytes,
+}
+
+/// Fetches the data of an offchain lookup from its gateways, trying them in turn
+async fn fetch_offchain_data(lookup: &OffchainLookup) -> Result<Bytes> {
+    let sender = lookup.sender.to_string().to_lowercase();
+    let data = hex::encode_prefixed(&lookup.callData);
+    let client = reqwest::Client::new();
+    let mut error = anyhow!("offchain lookup has no gateway");
+    for url in lookup.urls.iter() {
+        let gateway_url = url.replace("{sender}", &sender).replace("{data}", &data);
+        // gateways whose URL does not include the data expect it in a POST request
+        let request = if url.contains("{data}") {
+            client.get(gateway_url)
+        } else {
+            client
+                .post(gateway_url)
+                .json(&json!({"data": data, "sender": sender}))
+        };
+        match request.send().await.and_then(|r| r.error_for_status()) {
+            Ok(response) => return Ok(response.json::<GatewayResponse>().await?.data),
+            Err(e) => error = anyhow!("gateway {} failed: {}", url, e),
+        }
+    }
+    Err(error)
+}
+
+/// Calls `to` with `data`, following the offchain lookups it reverts with (EIP-3668)
+async fn call_with_offchain_lookup(env: &Env, to: Address, data: Bytes) -> Result<Bytes> {
+    let provider = env.get_provider();
+    let mut tx = TransactionRequest::default().with_to(to).with_input(data);
+    for _ in 0..=MAX_OFFCHAIN_LOOKUPS {
+        let err = match provider.call(tx.clone()).block(env.block()).await {
+            Ok(output) => return Ok(output),
+            Err(err) => err,
+        };
+        let lookup = match err
+            .as_error_resp()
+            .and_then(|e| e.as_decoded_error::<OffchainLookup>())
+        {
+            Some(lookup) if lookup.sender == to => lookup,
+            _ => return Err(err.into()),
+        };
+        let response = fetch_offchain_data(&lookup).await?;
+        let callback_data = [
+            lookup.callbackFunction.as_slice(),
+            &(response, lookup.extraData).abi_encode_params(),
+        ]
+        .concat();
+        tx = tx.with_input(callback_data);
+    }
+    bail!("too many offchain lookups when calling {}", to)
+}
+
+fn reverted_with<E: SolError>(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<RpcError<TransportErrorKind>>()
+        .and_then(|e| e.as_error_resp())
+        .and_then(|e| e.as_decoded_error::<E>())
+        .is_some()
+}
+
+/// Resolves an ENS name to the address it points to, through the UniversalResolver
+/// so that names using wildcard or offchain resolvers are resolved as well
+pub async fn resolve(env: &Env, name: &str) -> Result<Address> {
+    let normalized = normalize(name)?;
+    let data = EnsResolver::addrCall {
+        node: namehash(&normalized)?,
+    }
+    .abi_encode();
+    let call = UniversalResolver::resolveCall {
+        name: dns_encode(&normalized).into(),
+        data: data.into(),
+    };
+    let output =
+        match call_with_offchain_lookup(env, UNIVERSAL_RESOLVER, call.abi_encode().into()).await {
+            Ok(output) => output,
+            Err(e) if reverted_with::<UniversalResolver::ResolverNotFound>(&e) => {
+                bail!("no resolver found for {}", name)
+            }
+            Err(e) => return Err(e),
+        };
+    let resolved = UniversalResolver::resolveCall::abi_decode_returns(&output)?;
+    let addr = EnsResolver::addrCall::abi_decode_returns(&resolved.result)?;
+    if addr.is_zero() {
+        bail!("{} does not resolve to any address", name)
+    }
+    Ok(addr)
+}
+
+/// Returns the primary name of an address, checking that it resolves back to the address
+pub async fn lookup(env: &Env, addr: Address) -> Result<Option<String>> {
+    let reverse_name = format!("{}.addr.reverse", hex::encode(addr));
+    let node = namehash(&reverse_name)?;
+    let resolver_address = match get_resolver(env, node).await? {
+        Some(resolver) => resolver,
+        None => return Ok(None),
+    };
+    let resolver = EnsResolver::new(resolver_address, env.get_provider());
+    let name = resolver.name(node).block(env.block()).call().await?;
+    if name.is_empty() || resolve(env, &name).await.ok() != Some(addr) {
+        return Ok(None);
+    }
+    Ok(Some(name))
+}
+
+fn expects_address(type_: &Type) -> bool {
+    matches!(type_, Type::Address | Type::Contract(_))
+}
+
+/// Resolves the ENS names passed where the target type is an address or a contract
+pub async fn resolve_cast(env: &Env, type_: &Type, value: &Value) -> Result<Value> {
+    match value {
+        Value::Str(name) if expects_address(type_) && is_ens_name(name) => {
+            Ok(Value::Addr(resolve(env, name).await?))
+        }
+        _ => Ok(value.clone()),
+    }
+}
+
+/// Resolves the ENS names passed as arguments where any of the function variants expects an address
+pub async fn resolve_args(
+    env: &Env,
+    args: &[Value],
+    valid_args: &[Vec<FunctionParam>],
+) -> Result<Vec<Value>> {
+    let mut result = vec![];
+    for (i, arg) in args.iter().enumerate() {
+        let expected_address = valid_args
+            .iter()
+            .filter(|params| params.len() == args.len())
+            .any(|params| expects_address(params[i].get_type()));
+        if expected_address {
+            result.push(resolve_cast(env, &Type::Address, arg).await?);
+        } else {
+            result.push(arg.clone());
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::b256;
+
+    use super::*;
+
+    #[test]
+    fn test_namehash() {
+        assert_eq!(namehash("").unwrap(), B256::ZERO);
+        assert_eq!(
+            namehash("eth").unwrap(),
+            b256!("93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae")
+        );
+        assert_eq!(
+            namehash("foo.eth").unwrap(),
+            b256!("de9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f")
+        );
+        assert_eq!(namehash("Foo.ETH").unwrap(), namehash("foo.eth").unwrap());
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("Nick.ETH").unwrap(), "nick.eth");
+        assert_eq!(normalize("\u{ff46}oo.eth").unwrap(), "foo.eth");
+        assert_eq!(normalize("_dmarc.foo.eth").unwrap(), "_dmarc.foo.eth");
+        assert!(normalize("foo_bar.eth").is_err());
+        assert!(normalize("ab--c.eth").is_err());
+        assert!(normalize("xn--ls8h.eth").is_err());
+        assert!(normalize("foo..eth").is_err());
+    }
+
+    #[test]
+    fn test_dns_encode() {
+        assert_eq!(dns_encode(""), vec![0]);
+        assert_eq!(dns_encode("foo.eth"), b"\x03foo\x03eth\x00".to_vec());
+        let long_label = "a".repeat(256);
+        let encoded = dns_encode(&format!("{}.eth", long_label));
+        assert_eq!(encoded[0], 66);
+        assert_eq!(
+            &encoded[1..67],
+            format!("[{}]", hex::encode(keccak256(&long_label))).as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_is_ens_name() {
+        assert!(is_ens_name("vitalik.eth"));
+        assert!(is_ens_name("sub.vitalik.eth"));
+        assert!(!is_ens_name(".eth"));
+        assert!(!is_ens_name("vitalik"));
+        assert!(!is_ens_name("hello world.eth"));
+    }
+}
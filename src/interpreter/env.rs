@@ -2,12 +2,11 @@ use futures_util::lock::Mutex;
 use solang_parser::pt::{Expression, Identifier};
 use std::{
     collections::{HashMap, HashSet},
-    sync::Arc,
+    sync::{atomic::Ordering, Arc},
 };
-use url::Url;
 
 use alloy::{
-    eips::BlockId,
+    eips::{eip7702::Authorization, BlockId},
     json_abi,
     network::{AnyNetwork, EthereumWallet, NetworkWallet, TxSigner},
     node_bindings::{Anvil, AnvilInstance},
@@ -17,18 +16,55 @@ use alloy::{
         fillers::{BlobGasFiller, ChainIdFiller, FillProvider, GasFiller, JoinFill, NonceFiller},
         Identity, Provider, ProviderBuilder, RootProvider, WalletProvider,
     },
-    signers::{ledger::HDPath, Signature},
-    transports::http::{Client, Http},
+    rpc::client::RpcClient,
+    signers::{ledger::HDPath, local::PrivateKeySigner, Signature},
 };
 use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
 use coins_ledger::{transports::LedgerAsync, Ledger};
 
 use crate::{
     interpreter::Config,
+    loaders::types::StorageLayout,
     vendor::{ledger_signer::LedgerSigner, optional_wallet_filler::OptionalWalletFiller},
 };
 
-use super::{evaluate_expression, types::Type, ContractInfo, Value};
+use super::{
+    cache::RpcCache,
+    evaluate_expression,
+    fees::FeeConfig,
+    labels::AddressBook,
+    transport::{self, TransportContext},
+    types::Type,
+    utils::builtin_errors,
+    ContractInfo, Value,
+};
+
+/// Signer of a loaded wallet, used for messages, typed data and authorizations.
+/// The `Signer` trait is not imported as its methods conflict with the ones of `TxSigner`
+pub type MessageSigner = Arc<dyn WalletSigner>;
+
+/// Signing operations of loaded wallets not covered by the `Signer` trait
+#[async_trait]
+pub trait WalletSigner: alloy::signers::Signer + Send + Sync {
+    /// Signs an EIP-7702 authorization
+    async fn sign_authorization(&self, authorization: &Authorization) -> Result<Signature>;
+}
+
+#[async_trait]
+impl WalletSigner for PrivateKeySigner {
+    async fn sign_authorization(&self, authorization: &Authorization) -> Result<Signature> {
+        let hash = authorization.signature_hash();
+        Ok(alloy::signers::Signer::sign_hash(self, &hash).await?)
+    }
+}
+
+#[async_trait]
+impl WalletSigner for LedgerSigner {
+    async fn sign_authorization(&self, authorization: &Authorization) -> Result<Signature> {
+        Ok(self.sign_auth(authorization).await?)
+    }
+}
 
 type RecommendedFillerWithWallet = JoinFill<
     JoinFill<
@@ -37,17 +73,25 @@ type RecommendedFillerWithWallet = JoinFill<
     >,
     OptionalWalletFiller<EthereumWallet>,
 >;
-type EclairProvider = FillProvider<RecommendedFillerWithWallet, RootProvider>;
+pub type EclairProvider = FillProvider<RecommendedFillerWithWallet, RootProvider>;
 
 pub struct Env {
     variables: Vec<HashMap<String, Value>>,
     types: HashMap<String, Type>,
+    client: RpcClient,
+    /// Client looking up function signatures by selector
+    signatures_client: RpcClient,
+    transport: TransportContext,
     provider: EclairProvider,
+    chain_providers: HashMap<String, (RpcClient, EclairProvider)>,
     is_wallet_connected: bool,
     ledger: Option<Arc<Mutex<Ledger>>>,
     loaded_wallets: HashMap<Address, EthereumWallet>,
+    /// Signers of the loaded wallets, used to sign messages rather than transactions
+    signers: HashMap<Address, MessageSigner>,
     block_id: BlockId,
     contract_names: HashMap<Address, String>,
+    storage_layouts: HashMap<String, Arc<StorageLayout>>,
     events: HashMap<B256, json_abi::Event>,
     errors: HashMap<FixedBytes<4>, json_abi::Error>,
     functions: HashMap<FixedBytes<4>, json_abi::Function>,
@@ -55,25 +99,81 @@ pub struct Env {
     anvil: Option<AnvilInstance>,
     pub config: Config,
     account_aliases: HashMap<String, Address>,
+    /// Transactions sent from the REPL, with their chain, until they are mined or replaced
+    sent_transactions: Vec<(B256, Option<String>)>,
+    fee_config: FeeConfig,
+    /// Address books loaded for each chain id
+    address_books: HashMap<u64, AddressBook>,
+    /// Chain id of the current provider, known once its labels are loaded
+    chain_id: Option<u64>,
 }
 
 unsafe impl std::marker::Send for Env {}
 
 impl Env {
-    pub fn new(config: Config) -> Self {
-        let rpc_url = config.rpc_url.parse().unwrap();
+    /// Creates an environment connected to the configured HTTP RPC URL,
+    /// or replaying the configured recording
+    pub fn new(config: Config) -> Result<Self> {
+        let rpc_url = config.rpc_url.parse()?;
+        let transport = TransportContext::load(&config)?;
+        let client = transport::http_client(rpc_url, &config, &transport);
+        let signatures_client = transport::signatures_client(&transport);
+        Ok(Self::from_client(
+            config,
+            client,
+            signatures_client,
+            transport,
+        ))
+    }
+
+    /// Creates an environment connected to the configured RPC URL or endpoint alias,
+    /// using an HTTP, WebSocket or IPC transport depending on the URL
+    pub async fn connect(mut config: Config) -> Result<Self> {
+        config.rpc_url = config.resolve_rpc_url(&config.rpc_url)?;
+        let transport = TransportContext::load(&config)?;
+        let client = transport::connect_client(&config.rpc_url, &config, &transport).await?;
+        let signatures_client = transport::signatures_client(&transport);
+        Ok(Self::from_client(
+            config,
+            client,
+            signatures_client,
+            transport,
+        ))
+    }
+
+    /// Creates an environment sending all its requests through the given client,
+    /// including function signature lookups, which can use any transport,
+    /// such as a mocked one in tests.
+    /// Clients created when switching RPC URL or chain use the configuration instead
+    pub fn with_client(config: Config, client: RpcClient) -> Self {
+        let transport = TransportContext::new(&config);
+        Self::from_client(config, client.clone(), client, transport)
+    }
+
+    fn from_client(
+        config: Config,
+        client: RpcClient,
+        signatures_client: RpcClient,
+        transport: TransportContext,
+    ) -> Self {
         let provider = ProviderBuilder::new()
             .filler(OptionalWalletFiller::<EthereumWallet>::new())
-            .connect_http(rpc_url);
-        Env {
+            .connect_client(client.clone());
+        let mut env = Env {
             variables: vec![HashMap::new()],
             types: HashMap::new(),
+            client,
+            signatures_client,
+            transport,
             provider,
+            chain_providers: HashMap::new(),
             is_wallet_connected: false,
             ledger: None,
             loaded_wallets: HashMap::new(),
+            signers: HashMap::new(),
             block_id: BlockId::latest(),
             contract_names: HashMap::new(),
+            storage_layouts: HashMap::new(),
             events: HashMap::new(),
             errors: HashMap::new(),
             functions: HashMap::new(),
@@ -81,7 +181,15 @@ impl Env {
             anvil: None,
             config,
             account_aliases: HashMap::new(),
+            sent_transactions: vec![],
+            fee_config: FeeConfig::default(),
+            address_books: HashMap::new(),
+            chain_id: None,
+        };
+        for error in builtin_errors() {
+            env.register_error(error);
         }
+        env
     }
 
     pub fn push_scope(&mut self) {
@@ -92,8 +200,20 @@ impl Env {
         self.variables.pop();
     }
 
+    pub fn scope_depth(&self) -> usize {
+        self.variables.len()
+    }
+
+    /// Drops the scopes left by function calls that were interrupted
+    pub fn truncate_scopes(&mut self, depth: usize) {
+        self.variables.truncate(depth);
+    }
+
     pub fn set_debug(&mut self, debug: bool) {
         self.config.debug = debug;
+        self.transport
+            .logging
+            .store(debug || self.config.log_rpc, Ordering::Relaxed);
     }
 
     pub fn is_debug(&self) -> bool {
@@ -127,6 +247,15 @@ impl Env {
         contract_info
     }
 
+    pub fn add_storage_layout(&mut self, name: &str, layout: StorageLayout) {
+        self.storage_layouts
+            .insert(name.to_string(), Arc::new(layout));
+    }
+
+    pub fn get_storage_layout(&self, name: &str) -> Option<Arc<StorageLayout>> {
+        self.storage_layouts.get(name).cloned()
+    }
+
     pub fn list_events(&mut self) -> Vec<&json_abi::Event> {
         self.events.values().collect()
     }
@@ -159,8 +288,92 @@ impl Env {
         self.provider.clone()
     }
 
-    pub fn set_provider_url(&mut self, url: &str) -> Result<()> {
-        self.set_provider(None, url)
+    pub fn get_signatures_client(&self) -> RpcClient {
+        self.signatures_client.clone()
+    }
+
+    /// Returns the provider for the given RPC URL or endpoint alias, or the current provider
+    /// if no chain is given.
+    /// Providers are cached per URL and share the selected wallet, while keeping their own nonces
+    pub async fn get_chain_provider(&mut self, chain: Option<&str>) -> Result<EclairProvider> {
+        let rpc_url = match chain {
+            Some(chain) => self.config.resolve_rpc_url(chain)?,
+            None => return Ok(self.get_provider()),
+        };
+        if rpc_url == self.config.rpc_url {
+            return Ok(self.get_provider());
+        }
+        if let Some((_, provider)) = self.chain_providers.get(&rpc_url) {
+            return Ok(provider.clone());
+        }
+        let client = self.connect_client(&rpc_url).await?;
+        let provider = self.build_provider(client.clone(), None);
+        self.chain_providers
+            .insert(rpc_url, (client, provider.clone()));
+        Ok(provider)
+    }
+
+    /// Same as `get_chain_provider` but only returns providers that are already connected
+    pub fn get_connected_chain_provider(&self, chain: Option<&str>) -> Result<EclairProvider> {
+        let rpc_url = match chain {
+            Some(chain) => self.config.resolve_rpc_url(chain)?,
+            None => return Ok(self.get_provider()),
+        };
+        if rpc_url == self.config.rpc_url {
+            return Ok(self.get_provider());
+        }
+        self.chain_providers
+            .get(&rpc_url)
+            .map(|(_, provider)| provider.clone())
+            .ok_or(anyhow!("not connected to {}", chain.unwrap_or_default()))
+    }
+
+    pub async fn set_provider_url(&mut self, url: &str) -> Result<()> {
+        let rpc_url = self.config.resolve_rpc_url(url)?;
+        self.client = self.connect_client(&rpc_url).await?;
+        self.config.rpc_url = rpc_url;
+        self.set_provider(None);
+        self.anvil = None;
+        if let Err(e) = self.load_labels().await {
+            eprintln!("warning: could not load labels: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Loads the address book of the chain the environment is connected to,
+    /// unless it was already loaded when previously connected to the chain
+    pub async fn load_labels(&mut self) -> Result<()> {
+        self.chain_id = None;
+        let chain_id = self.get_chain_id().await?;
+        if !self.address_books.contains_key(&chain_id) {
+            self.address_books
+                .insert(chain_id, AddressBook::load(chain_id)?);
+        }
+        self.chain_id = Some(chain_id);
+        Ok(())
+    }
+
+    /// Returns the address book of the current chain, if loaded
+    pub fn get_address_book(&self) -> Option<&AddressBook> {
+        self.chain_id
+            .and_then(|chain_id| self.address_books.get(&chain_id))
+    }
+
+    pub fn get_address_book_mut(&mut self) -> Result<&mut AddressBook> {
+        self.chain_id
+            .and_then(|chain_id| self.address_books.get_mut(&chain_id))
+            .ok_or(anyhow!("labels can only be used once connected to a chain"))
+    }
+
+    pub fn get_label(&self, addr: &Address) -> Option<String> {
+        self.get_address_book()
+            .and_then(|book| book.get_label(addr))
+            .cloned()
+    }
+
+    /// Formats a value for the REPL output, showing the labels of the addresses it contains
+    pub fn format_value(&self, value: &Value) -> String {
+        value.format_with_labels(&|addr| self.get_label(addr))
     }
 
     pub async fn get_chain_id(&self) -> Result<u64> {
@@ -176,7 +389,7 @@ impl Env {
         }
         .try_spawn()?;
         let endpoint = anvil.endpoint();
-        self.set_provider_url(endpoint.as_str())?;
+        self.set_provider_url(endpoint.as_str()).await?;
         self.anvil = Some(anvil);
         Ok(())
     }
@@ -204,43 +417,36 @@ impl Env {
         Ok(())
     }
 
-    pub async fn load_ledger(&mut self, index: usize) -> Result<()> {
+    pub async fn load_ledger(&mut self, path: HDPath) -> Result<()> {
         self.init_ledger().await?;
         let chain_id = self.get_chain_id().await?;
-        let signer = LedgerSigner::new(
-            self.ledger.as_ref().unwrap().clone(),
-            HDPath::LedgerLive(index),
-            Some(chain_id),
-        )
-        .await?;
-        self.set_wallet(signer)
+        let signer =
+            LedgerSigner::new(self.ledger.as_ref().unwrap().clone(), path, Some(chain_id)).await?;
+        self.set_wallet_signer(signer)
     }
 
-    pub async fn list_ledger_wallets(&mut self, count: usize) -> Result<Vec<Address>> {
+    pub async fn list_ledger_wallets(&mut self, paths: &[HDPath]) -> Result<Vec<Address>> {
+        let first_path = match paths.first() {
+            Some(path) => path.clone(),
+            None => return Ok(vec![]),
+        };
         self.init_ledger().await?;
-        let signer = LedgerSigner::new(
-            self.ledger.as_ref().unwrap().clone(),
-            HDPath::LedgerLive(0),
-            None,
-        )
-        .await?;
+        let signer =
+            LedgerSigner::new(self.ledger.as_ref().unwrap().clone(), first_path, None).await?;
         let mut wallets = vec![signer.address()];
-        for i in 1..count {
-            let addr = signer.get_address_with_path(&HDPath::LedgerLive(i)).await?;
+        for path in &paths[1..] {
+            let addr = signer.get_address_with_path(path).await?;
             wallets.push(addr);
         }
         Ok(wallets)
     }
 
+    pub fn rpc_cache(&self) -> &RpcCache {
+        &self.transport.cache
+    }
+
     pub fn get_rpc_url(&self) -> String {
-        self.provider
-            .client()
-            .transport()
-            .as_any()
-            .downcast_ref::<Http<Client>>()
-            .unwrap()
-            .url()
-            .to_string()
+        self.config.rpc_url.clone()
     }
 
     pub fn get_default_sender(&self) -> Option<Address> {
@@ -255,11 +461,37 @@ impl Env {
         }
     }
 
+    /// Loads and selects a wallet that can only sign transactions
     pub fn set_signer<S>(&mut self, signer: S) -> Result<()>
     where
         S: TxSigner<Signature> + Send + Sync + 'static,
     {
-        self.set_wallet(signer)
+        let wallet = EthereumWallet::from(signer);
+        let address = NetworkWallet::<AnyNetwork>::default_signer_address(&wallet);
+        self.signers.remove(&address);
+        self.set_wallet(address, wallet)
+    }
+
+    /// Loads and selects a wallet that can also sign messages, typed data and authorizations
+    pub fn set_wallet_signer<S>(&mut self, signer: S) -> Result<()>
+    where
+        S: TxSigner<Signature> + WalletSigner + Clone + 'static,
+    {
+        let wallet = EthereumWallet::from(signer.clone());
+        let address = NetworkWallet::<AnyNetwork>::default_signer_address(&wallet);
+        self.signers.insert(address, Arc::new(signer));
+        self.set_wallet(address, wallet)
+    }
+
+    /// Returns the signer of the selected wallet
+    pub fn get_signer(&self) -> Result<MessageSigner> {
+        let address = self
+            .get_default_sender()
+            .ok_or(anyhow!("no wallet selected"))?;
+        self.signers
+            .get(&address)
+            .cloned()
+            .ok_or(anyhow!("wallet {} can only sign transactions", address))
     }
 
     pub fn set_type(&mut self, name: &str, type_: Type) {
@@ -284,6 +516,27 @@ impl Env {
         Vec::from_iter(vars)
     }
 
+    pub fn track_transaction(&mut self, hash: B256, chain: Option<String>) {
+        self.sent_transactions.push((hash, chain));
+    }
+
+    pub fn untrack_transactions(&mut self, hashes: &[B256]) {
+        self.sent_transactions
+            .retain(|(hash, _)| !hashes.contains(hash));
+    }
+
+    pub fn get_sent_transactions(&self) -> Vec<(B256, Option<String>)> {
+        self.sent_transactions.clone()
+    }
+
+    pub fn get_fee_config(&self) -> &FeeConfig {
+        &self.fee_config
+    }
+
+    pub fn set_fee_config(&mut self, fee_config: FeeConfig) {
+        self.fee_config = fee_config;
+    }
+
     pub fn get_contract_name(&self, addr: &Address) -> Option<&String> {
         self.contract_names.get(addr)
     }
@@ -319,7 +572,7 @@ impl Env {
         let is_top_level = self.variables.len() == 1;
         let scope = self.variables.last_mut().unwrap();
         if is_top_level {
-            if let Value::Contract(ContractInfo(name, _), addr) = &value {
+            if let Value::Contract(ContractInfo(name, _), addr, _) = &value {
                 self.contract_names.insert(*addr, name.clone());
             }
         }
@@ -363,12 +616,7 @@ impl Env {
         self.select_wallet(*address)
     }
 
-    fn set_wallet<S>(&mut self, signer: S) -> Result<()>
-    where
-        S: TxSigner<Signature> + Send + Sync + 'static,
-    {
-        let wallet = EthereumWallet::from(signer);
-        let address = NetworkWallet::<AnyNetwork>::default_signer_address(&wallet);
+    fn set_wallet(&mut self, address: Address, wallet: EthereumWallet) -> Result<()> {
         self.loaded_wallets.insert(address, wallet.clone());
         self.is_wallet_connected = true;
         self._select_wallet(wallet)
@@ -379,33 +627,34 @@ impl Env {
     }
 
     fn _select_wallet(&mut self, wallet: EthereumWallet) -> Result<()> {
-        self.set_provider(Some(wallet), &self.get_rpc_url())
-    }
-
-    fn set_provider(&mut self, wallet: Option<EthereumWallet>, url: &str) -> Result<()> {
-        let rpc_url = match url.parse() {
-            Ok(u) => u,
-            Err(_) => self
-                .config
-                .rpc_endpoints
-                .get(url)
-                .ok_or(anyhow!("invalid URL and no config for {}", url))
-                .and_then(|u| u.parse::<Url>().map_err(Into::into))?,
-        };
-        self.config.rpc_url = rpc_url.to_string();
+        self.set_provider(Some(wallet));
+        Ok(())
+    }
+
+    fn set_provider(&mut self, wallet: Option<EthereumWallet>) {
+        self.provider = self.build_provider(self.client.clone(), wallet);
+        let chain_providers = std::mem::take(&mut self.chain_providers);
+        self.chain_providers = chain_providers
+            .into_iter()
+            .map(|(url, (client, _))| (url, (client.clone(), self.build_provider(client, None))))
+            .collect();
+    }
 
+    async fn connect_client(&self, rpc_url: &str) -> Result<RpcClient> {
+        transport::connect_client(rpc_url, &self.config, &self.transport).await
+    }
+
+    /// Builds a provider using the given wallet, or the selected one if any
+    fn build_provider(&self, client: RpcClient, wallet: Option<EthereumWallet>) -> EclairProvider {
         let mut wallet_filler = OptionalWalletFiller::new();
         if let Some(w) = wallet {
             wallet_filler.set_wallet(w);
         } else if self.is_wallet_connected {
             wallet_filler.set_wallet(self.provider.wallet().clone());
         }
-        let provider = ProviderBuilder::new()
+        ProviderBuilder::new()
             .filler(wallet_filler)
-            .connect_http(rpc_url);
-        self.provider = provider;
-        self.anvil = None;
-        Ok(())
+            .connect_client(client)
     }
 
     async fn init_ledger(&mut self) -> Result<()> {
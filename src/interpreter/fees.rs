@@ -0,0 +1,193 @@
+use std::{fmt::Display, str::FromStr};
+
+use alloy::{
+    eips::BlockNumberOrTag, network::TransactionBuilder, primitives::U256, providers::Provider,
+    rpc::types::TransactionRequest,
+};
+use anyhow::{anyhow, bail, Result};
+
+use super::EclairProvider;
+
+/// Number of past blocks used to estimate the priority fee of a strategy
+const FEE_HISTORY_BLOCKS: u64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeStrategy {
+    Slow,
+    Normal,
+    Fast,
+}
+
+impl FeeStrategy {
+    /// Percentile of the priority fees paid in recent blocks to use
+    pub const fn reward_percentile(&self) -> f64 {
+        match self {
+            FeeStrategy::Slow => 10.0,
+            FeeStrategy::Normal => 50.0,
+            FeeStrategy::Fast => 90.0,
+        }
+    }
+}
+
+impl FromStr for FeeStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "slow" => Ok(FeeStrategy::Slow),
+            "normal" => Ok(FeeStrategy::Normal),
+            "fast" => Ok(FeeStrategy::Fast),
+            _ => bail!("unknown fee strategy {}, expected slow, normal or fast", s),
+        }
+    }
+}
+
+impl Display for FeeStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeeStrategy::Slow => write!(f, "slow"),
+            FeeStrategy::Normal => write!(f, "normal"),
+            FeeStrategy::Fast => write!(f, "fast"),
+        }
+    }
+}
+
+/// Fee settings applied to all the transactions sent from the REPL,
+/// unless the fees are passed explicitly as options
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeeConfig {
+    pub strategy: Option<FeeStrategy>,
+    /// Maximum fee per gas (or gas price) above which transactions are not signed
+    pub max_fee_cap: Option<u128>,
+    /// Multiplier of the estimated gas to use as the gas limit,
+    /// as its digits and number of decimals
+    pub gas_limit_multiplier: Option<(U256, usize)>,
+}
+
+impl FeeConfig {
+    /// Fills the gas limit and fees of the transaction according to the configuration
+    /// and fails if the resulting fees are above the cap
+    pub async fn apply(
+        &self,
+        provider: &EclairProvider,
+        mut tx_req: TransactionRequest,
+    ) -> Result<TransactionRequest> {
+        if let (Some((digits, decimals)), None) = (self.gas_limit_multiplier, tx_req.gas) {
+            let gas = provider.estimate_gas(tx_req.clone()).await?;
+            let gas_limit = U256::from(gas)
+                .checked_mul(digits)
+                .map(|gas| gas / U256::from(10).pow(U256::from(decimals)))
+                .and_then(|gas| u64::try_from(gas).ok())
+                .ok_or(anyhow!("gas limit overflows"))?;
+            tx_req = tx_req.with_gas_limit(gas_limit);
+        }
+
+        let is_legacy = tx_req.gas_price.is_some() || tx_req.transaction_type == Some(0);
+        let needs_fees =
+            tx_req.max_fee_per_gas.is_none() || tx_req.max_priority_fee_per_gas.is_none();
+        if !is_legacy && needs_fees && (self.strategy.is_some() || self.max_fee_cap.is_some()) {
+            let (base_fee, priority_fee) = match self.strategy {
+                Some(strategy) => estimate_fees(provider, strategy).await?,
+                None => {
+                    // the default estimator uses twice the base fee plus the priority fee
+                    let estimate = provider.estimate_eip1559_fees().await?;
+                    let priority_fee = estimate.max_priority_fee_per_gas;
+                    ((estimate.max_fee_per_gas - priority_fee) / 2, priority_fee)
+                }
+            };
+            let priority_fee = match (tx_req.max_priority_fee_per_gas, tx_req.max_fee_per_gas) {
+                (Some(priority_fee), _) => priority_fee,
+                // the priority fee cannot be above an explicit max fee
+                (None, Some(max_fee)) => priority_fee.min(max_fee),
+                (None, None) => priority_fee,
+            };
+            let max_fee = match self.max_fee_cap {
+                Some(cap) if cap < base_fee + priority_fee => bail!(
+                    "current fees ({} wei) are above the max fee cap ({} wei)",
+                    base_fee + priority_fee,
+                    cap
+                ),
+                Some(cap) => cap.min(2 * base_fee + priority_fee),
+                None => 2 * base_fee + priority_fee,
+            };
+            tx_req = tx_req
+                .with_max_priority_fee_per_gas(priority_fee)
+                .with_max_fee_per_gas(tx_req.max_fee_per_gas.unwrap_or(max_fee));
+        }
+
+        self.check_cap(&tx_req)?;
+        Ok(tx_req)
+    }
+
+    /// Fails if the fees of the transaction are above the configured cap
+    pub fn check_cap(&self, tx_req: &TransactionRequest) -> Result<()> {
+        let cap = match self.max_fee_cap {
+            Some(cap) => cap,
+            None => return Ok(()),
+        };
+        if let Some(fee) = tx_req.max_fee_per_gas.or(tx_req.gas_price) {
+            if fee > cap {
+                bail!(
+                    "transaction fee ({} wei) is above the max fee cap ({} wei)",
+                    fee,
+                    cap
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the base fee of the next block and the median of the priority fees
+/// paid at the percentile of the strategy over the last blocks
+async fn estimate_fees(provider: &EclairProvider, strategy: FeeStrategy) -> Result<(u128, u128)> {
+    let history = provider
+        .get_fee_history(
+            FEE_HISTORY_BLOCKS,
+            BlockNumberOrTag::Latest,
+            &[strategy.reward_percentile()],
+        )
+        .await?;
+    let base_fee = history
+        .base_fee_per_gas
+        .last()
+        .copied()
+        .ok_or(anyhow!("fee history returned no base fee"))?;
+    let mut rewards = history
+        .reward
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|rewards| rewards.first().copied())
+        .filter(|reward| *reward > 0)
+        .collect::<Vec<_>>();
+    rewards.sort();
+    let priority_fee = rewards.get(rewards.len() / 2).copied().unwrap_or_default();
+    Ok((base_fee, priority_fee))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fee_strategy() {
+        assert_eq!("fast".parse::<FeeStrategy>().unwrap(), FeeStrategy::Fast);
+        assert_eq!(FeeStrategy::Slow.to_string(), "slow");
+        assert!("instant".parse::<FeeStrategy>().is_err());
+    }
+
+    #[test]
+    fn test_check_cap() {
+        let config = FeeConfig {
+            max_fee_cap: Some(100),
+            ..Default::default()
+        };
+        let tx_req = TransactionRequest::default().with_max_fee_per_gas(100);
+        assert!(config.check_cap(&tx_req).is_ok());
+        let tx_req = TransactionRequest::default().with_max_fee_per_gas(101);
+        assert!(config.check_cap(&tx_req).is_err());
+        let tx_req = TransactionRequest::default().with_gas_price(101);
+        assert!(config.check_cap(&tx_req).is_err());
+        assert!(FeeConfig::default().check_cap(&tx_req).is_ok());
+    }
+}
@@ -1,14 +1,15 @@
 use std::{hash::Hash, sync::Arc};
 
 use alloy::{
+    consensus::TxType,
     contract::{CallBuilder, ContractInstance, Interface},
-    eips::{BlockId, BlockNumberOrTag},
+    eips::{eip7702::SignedAuthorization, BlockId, BlockNumberOrTag},
     json_abi::StateMutability,
     network::{Network, TransactionBuilder},
     primitives::{keccak256, Address, Bytes, FixedBytes, U256},
     providers::{ext::DebugApi, Provider},
     rpc::types::{
-        trace::geth::{self, GethDebugTracingCallOptions},
+        trace::geth::{self, CallFrame, GethDebugTracingCallOptions},
         TransactionInput, TransactionRequest,
     },
 };
@@ -17,8 +18,10 @@ use futures::{future::BoxFuture, FutureExt};
 use itertools::Itertools;
 
 use crate::interpreter::{
-    tracing::format_call_frame, types::HashableIndexMap, utils::decode_error, ContractInfo, Env,
-    Type, Value,
+    tracing::format_call_frame,
+    types::HashableIndexMap,
+    utils::{decode_error, format_revert},
+    ContractInfo, EclairProvider, Env, Type, Value,
 };
 
 use super::{Function, FunctionDef, FunctionParam};
@@ -64,9 +67,13 @@ pub struct CallOptions {
     block: Option<BlockId>,
     from: Option<Address>,
     gas_limit: Option<u64>,
+    nonce: Option<u64>,
     max_fee: Option<u128>,
     priority_fee: Option<u128>,
     gas_price: Option<u128>,
+    authorization_list: Option<Vec<SignedAuthorization>>,
+    tx_type: Option<TxType>,
+    trace: bool,
 }
 
 impl CallOptions {
@@ -75,6 +82,12 @@ impl CallOptions {
             bail!("block is only available for calls");
         } else if self.from.is_some() {
             bail!("from is only available for calls");
+        } else if self.tx_type == Some(TxType::Legacy)
+            && (self.max_fee.is_some() || self.priority_fee.is_some())
+        {
+            bail!("maxFee and priorityFee are only available for eip1559 transactions");
+        } else if self.tx_type == Some(TxType::Eip1559) && self.gas_price.is_some() {
+            bail!("gasPrice is only available for legacy transactions");
         } else {
             Ok(())
         }
@@ -87,10 +100,58 @@ impl CallOptions {
             bail!("priorityFee is only available for sends");
         } else if self.gas_price.is_some() {
             bail!("gasPrice is only available for sends");
+        } else if self.authorization_list.is_some() {
+            bail!("authorizationList is only available for sends");
+        } else if self.nonce.is_some() {
+            bail!("nonce is only available for sends");
+        } else if self.tx_type.is_some() {
+            bail!("type is only available for sends");
+        } else if self.trace {
+            bail!("trace is only available for sends, use traceCall instead");
         } else {
             Ok(())
         }
     }
+
+    pub fn value(&self) -> Option<U256> {
+        self.value
+    }
+
+    /// Applies the type, nonce, gas and authorization options of a send to the transaction,
+    /// then the fee configuration of the environment
+    pub async fn apply_to_send(
+        &self,
+        env: &Env,
+        provider: &EclairProvider,
+        mut tx_req: TransactionRequest,
+    ) -> Result<TransactionRequest> {
+        if let Some(gas) = self.gas_limit {
+            tx_req = tx_req.with_gas_limit(gas);
+        }
+        if let Some(nonce) = self.nonce {
+            tx_req = tx_req.with_nonce(nonce);
+        }
+        if let Some(gas_price) = self.gas_price {
+            tx_req = tx_req.with_gas_price(gas_price);
+        }
+        if let Some(max_fee) = self.max_fee {
+            tx_req = tx_req.with_max_fee_per_gas(max_fee);
+        }
+        if let Some(priority_fee) = self.priority_fee {
+            tx_req = tx_req.with_max_priority_fee_per_gas(priority_fee);
+        }
+        if let Some(authorization_list) = &self.authorization_list {
+            tx_req.authorization_list = Some(authorization_list.clone());
+        }
+        match self.tx_type {
+            Some(TxType::Legacy) if tx_req.gas_price.is_none() => {
+                tx_req = tx_req.with_gas_price(provider.get_gas_price().await?);
+            }
+            _ => {}
+        }
+        tx_req.transaction_type = self.tx_type.map(u8::from);
+        env.get_fee_config().apply(provider, tx_req).await
+    }
 }
 
 impl Hash for CallOptions {
@@ -114,6 +175,22 @@ impl std::fmt::Display for CallOptions {
     }
 }
 
+fn parse_tx_type(value: &Value) -> Result<TxType> {
+    match value {
+        Value::Str(s) if s == "legacy" => Ok(TxType::Legacy),
+        Value::Str(s) if s == "eip1559" => Ok(TxType::Eip1559),
+        n if n.is_number() => match n.as_u64()? {
+            0 => Ok(TxType::Legacy),
+            2 => Ok(TxType::Eip1559),
+            t => bail!("unsupported transaction type {}", t),
+        },
+        _ => bail!(
+            "unsupported transaction type {}, expected legacy or eip1559",
+            value
+        ),
+    }
+}
+
 impl TryFrom<&HashableIndexMap<String, Value>> for CallOptions {
     type Error = anyhow::Error;
 
@@ -125,9 +202,21 @@ impl TryFrom<&HashableIndexMap<String, Value>> for CallOptions {
                 "block" => opts.block = Some(v.as_block_id()?),
                 "from" => opts.from = Some(v.as_address()?),
                 "gasLimit" => opts.gas_limit = Some(v.as_u64()?),
+                "nonce" => opts.nonce = Some(v.as_u64()?),
                 "gasPrice" => opts.gas_price = Some(v.as_u128()?),
                 "maxFee" => opts.max_fee = Some(v.as_u128()?),
                 "priorityFee" => opts.priority_fee = Some(v.as_u128()?),
+                "authorizationList" => {
+                    opts.authorization_list = Some(match v {
+                        Value::Array(items, _) => items
+                            .iter()
+                            .map(Value::as_authorization)
+                            .collect::<Result<_>>()?,
+                        _ => vec![v.as_authorization()?],
+                    })
+                }
+                "type" => opts.tx_type = Some(parse_tx_type(v)?),
+                "trace" => opts.trace = v.as_bool()?,
                 _ => bail!("unexpected key {}", k),
             }
         }
@@ -182,7 +271,7 @@ impl FunctionDef for ContractFunction {
     }
 
     fn get_valid_args(&self, receiver: &Option<Value>) -> Vec<Vec<FunctionParam>> {
-        let (ContractInfo(_, abi), _) = receiver.clone().unwrap().as_contract().unwrap();
+        let (ContractInfo(_, abi), _, _) = receiver.clone().unwrap().as_contract().unwrap();
         let functions = abi.function(&self.func_name).cloned().unwrap_or(vec![]);
 
         functions
@@ -213,7 +302,7 @@ impl FunctionDef for ContractFunction {
         values: &'a [Value],
         options: &'a HashableIndexMap<String, Value>,
     ) -> BoxFuture<'a, Result<Value>> {
-        let (ContractInfo(_, abi), addr) = values[0].as_contract().unwrap();
+        let (ContractInfo(_, abi), addr, chain) = values[0].as_contract().unwrap();
         let types_ = values[1..].iter().map(Value::get_type).collect::<Vec<_>>();
         let selector = self.get_selector(&types_);
 
@@ -222,10 +311,19 @@ impl FunctionDef for ContractFunction {
                 .functions()
                 .find(|f| f.selector() == selector)
                 .ok_or_else(|| anyhow!("function {} not found", self.get_signature(&types_)))?;
-            let interface = Interface::new(abi.clone());
-            let contract =
-                ContractInstance::new(addr, env.get_provider().root().clone(), interface);
             let call_options: CallOptions = options.try_into()?;
+            if chain.is_some() && (self.mode == ContractCallMode::TraceCall || call_options.trace) {
+                bail!("tracing is only supported for contracts on the current chain");
+            }
+            let provider = env.get_chain_provider(chain.as_deref()).await?;
+            // the block set with vm.block only applies to the current chain
+            let default_block = if chain.is_some() {
+                BlockId::latest()
+            } else {
+                env.block()
+            };
+            let interface = Interface::new(abi.clone());
+            let contract = ContractInstance::new(addr, provider.root().clone(), interface);
             let tokens = values[1..]
                 .iter()
                 .map(|arg| arg.try_into())
@@ -242,9 +340,10 @@ impl FunctionDef for ContractFunction {
             } else if self.mode == ContractCallMode::Call
                 || (self.mode == ContractCallMode::Default && is_view)
             {
-                _execute_contract_call(&addr, func, &call_options, env).await
+                _execute_contract_call(&addr, func, &call_options, &provider, default_block).await
             } else {
-                _execute_contract_send(&addr, func, &call_options, env).await
+                _execute_contract_send(&addr, func, &call_options, env, &provider, chain.clone())
+                    .await
             }
         }
         .boxed()
@@ -278,7 +377,9 @@ async fn _execute_contract_send<P, N>(
     addr: &Address,
     func: CallBuilder<P, alloy::json_abi::Function, N>,
     opts: &CallOptions,
-    env: &Env,
+    env: &mut Env,
+    provider: &EclairProvider,
+    chain: Option<String>,
 ) -> Result<Value>
 where
     P: Provider<N>,
@@ -289,20 +390,30 @@ where
     let from_ = env
         .get_default_sender()
         .ok_or(anyhow!("no wallet connected"))?;
-    tx_req = tx_req.with_from(from_);
-    if let Some(gas_price) = opts.gas_price.as_ref() {
-        tx_req = tx_req.with_gas_price(*gas_price);
-    }
-    if let Some(max_fee) = opts.max_fee.as_ref() {
-        tx_req = tx_req.with_max_fee_per_gas(*max_fee);
-    }
-    if let Some(priority_fee) = opts.priority_fee.as_ref() {
-        tx_req = tx_req.with_max_priority_fee_per_gas(*priority_fee);
+    tx_req = opts
+        .apply_to_send(env, provider, tx_req.with_from(from_))
+        .await?;
+
+    let err = match provider.send_transaction(tx_req.clone()).await {
+        Ok(tx) => {
+            env.track_transaction(*tx.tx_hash(), chain.clone());
+            return Ok(Value::Transaction(*tx.tx_hash(), chain));
+        }
+        Err(err) => err,
+    };
+    let revert_data = match err.as_error_resp().and_then(|e| e.as_revert_data()) {
+        Some(data) => data,
+        None => return Err(err.into()),
+    };
+    let reason = format_revert(env, &revert_data).await;
+    if opts.trace {
+        // tracing is best-effort, e.g. the node may not support `debug_traceCall`
+        match _trace_call(env, tx_req, BlockId::latest()).await {
+            Ok(call_frame) => println!("{}", format_call_frame(env, &call_frame).await),
+            Err(err) => eprintln!("could not trace the transaction: {}", err),
+        }
     }
-
-    let provider = env.get_provider();
-    let tx = provider.send_transaction(tx_req).await?;
-    Ok(Value::Transaction(*tx.tx_hash()))
+    bail!("revert: {}", reason)
 }
 
 fn _decode_output<P, N>(
@@ -329,7 +440,8 @@ async fn _execute_contract_call<P, N>(
     addr: &Address,
     func: CallBuilder<P, alloy::json_abi::Function, N>,
     opts: &CallOptions,
-    env: &Env,
+    provider: &EclairProvider,
+    default_block: BlockId,
 ) -> Result<Value>
 where
     P: Provider<N>,
@@ -340,8 +452,7 @@ where
     if let Some(from_) = opts.from {
         tx_req = tx_req.with_from(from_);
     }
-    let block = opts.block.unwrap_or(env.block());
-    let provider = env.get_provider();
+    let block = opts.block.unwrap_or(default_block);
     let return_bytes = provider.call(tx_req).block(block).await?;
     _decode_output(return_bytes, func)
 }
@@ -367,6 +478,31 @@ where
     }
 
     let block_tag = opts.block.unwrap_or(env.block());
+    let call_frame = _trace_call(env, tx_req, block_tag).await?;
+
+    println!("{}", format_call_frame(env, &call_frame).await);
+
+    if let Some(err) = call_frame.error {
+        if let Some(output) = call_frame.output {
+            if let Ok(err_val) = decode_error(env, &output) {
+                bail!("revert: {}", err_val);
+            } else {
+                bail!("revert: {}", output);
+            }
+        }
+        bail!("revert: {}", err);
+    } else if let Some(output) = call_frame.output {
+        _decode_output(output, func)
+    } else {
+        Ok(Value::Null)
+    }
+}
+
+async fn _trace_call(
+    env: &mut Env,
+    tx_req: TransactionRequest,
+    block_tag: BlockId,
+) -> Result<CallFrame> {
     let block = env
         .get_provider()
         .get_block(block_tag)
@@ -394,24 +530,7 @@ where
         .debug_trace_call(tx_req, BlockId::Number(block_num_tag), options)
         .await;
     if let Some(url) = previous_url {
-        env.set_provider_url(url.as_str())?;
-    }
-    let call_frame = maybe_tx?.try_into_call_frame()?;
-
-    println!("{}", format_call_frame(env, &call_frame));
-
-    if let Some(err) = call_frame.error {
-        if let Some(output) = call_frame.output {
-            if let Ok(err_val) = decode_error(env, &output) {
-                bail!("revert: {}", err_val);
-            } else {
-                bail!("revert: {}", output);
-            }
-        }
-        bail!("revert: {}", err);
-    } else if let Some(output) = call_frame.output {
-        _decode_output(output, func)
-    } else {
-        Ok(Value::Null)
+        env.set_provider_url(url.as_str()).await?;
     }
+    Ok(maybe_tx?.try_into_call_frame()?)
 }
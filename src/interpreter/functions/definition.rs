@@ -21,6 +21,15 @@ pub trait FunctionDef: std::fmt::Debug + Send + Sync {
     fn member_access(&self, _receiver: &Option<Value>, _member: &str) -> Option<Value> {
         None
     }
+
+    fn index<'a>(
+        &'a self,
+        _env: &'a mut Env,
+        _receiver: &'a Option<Value>,
+        _key: &'a Value,
+    ) -> BoxFuture<'a, Result<Value>> {
+        async move { Err(anyhow!("{} cannot be indexed", self.name())) }.boxed()
+    }
 }
 
 #[derive(Debug)]
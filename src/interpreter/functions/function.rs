@@ -1,4 +1,4 @@
-use crate::interpreter::{types::HashableIndexMap, utils::join_with_final, Env, Value};
+use crate::interpreter::{ens, types::HashableIndexMap, utils::join_with_final, Env, Value};
 use anyhow::{anyhow, bail, Result};
 use itertools::Itertools;
 use std::{fmt, sync::Arc};
@@ -68,6 +68,10 @@ impl Function {
             .ok_or(anyhow!("no member {} for {}", member, self))
     }
 
+    pub async fn index(&self, env: &mut Env, key: &Value) -> Result<Value> {
+        self.def.index(env, &self.receiver, key).await
+    }
+
     pub fn with_opts(self, opts: HashableIndexMap<String, Value>) -> Self {
         let mut new = self;
         new.options = opts;
@@ -111,7 +115,9 @@ impl Function {
     }
 
     pub async fn execute_in_current_scope(&self, env: &mut Env, args: &[Value]) -> Result<Value> {
-        let mut unified_args = self.get_unified_args(args)?;
+        let valid_args = self.def.get_valid_args(&self.receiver);
+        let args = ens::resolve_args(env, args, &valid_args).await?;
+        let mut unified_args = self.get_unified_args(&args)?;
         if let Some(receiver) = &self.receiver {
             unified_args.insert(0, receiver.clone());
         }
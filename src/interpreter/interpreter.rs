@@ -14,10 +14,11 @@ use crate::loaders::types::Project;
 
 use super::assignment::Lhs;
 use super::builtins;
+use super::ens;
 use super::functions::{AnonymousFunction, FunctionDef, UserDefinedFunction};
 use super::parsing::ParsedCode;
 use super::types::{ArrayIndex, HashableIndexMap, Type};
-use super::utils::parse_rational_literal;
+use super::utils::parse_decimal_literal;
 use super::{env::Env, parsing, value::Value};
 
 pub const SETUP_FUNCTION_NAME: &str = "setUp";
@@ -68,6 +69,9 @@ pub fn load_project(env: &mut Env, project: &Project) -> Result<()> {
     for contract_name in project.contract_names().iter() {
         let contract = project.get_contract(contract_name);
         env.add_contract(contract_name, contract.clone());
+        if let Some(layout) = project.get_storage_layout(contract_name) {
+            env.add_storage_layout(contract_name, layout.clone());
+        }
     }
     Ok(())
 }
@@ -322,8 +326,7 @@ pub fn evaluate_expression(env: &mut Env, expr: Box<Expression>) -> BoxFuture<'_
             Expression::HexNumberLiteral(_, n, _) => Value::from_hex(n),
 
             Expression::RationalNumberLiteral(_, whole, raw_fraction, raw_exponent, _) => {
-                parse_rational_literal(&whole, &raw_fraction, &raw_exponent)
-                    .map(|v| Value::Uint(v, 256))
+                parse_decimal_literal(&whole, &raw_fraction, &raw_exponent)
             }
 
             Expression::And(_, lexpr, rexpr) => {
@@ -396,7 +399,7 @@ pub fn evaluate_expression(env: &mut Env, expr: Box<Expression>) -> BoxFuture<'_
             }
 
             Expression::Variable(var) => {
-                let id = var.to_string();
+                let id = parsing::unescape_identifier(&var.name).to_string();
                 if let Some(result) = env.get_var(&id) {
                     Ok(result.clone())
                 } else if let Some(type_) = env.get_type(&id) {
@@ -408,7 +411,7 @@ pub fn evaluate_expression(env: &mut Env, expr: Box<Expression>) -> BoxFuture<'_
 
             Expression::MemberAccess(_, receiver_expr, method) => {
                 let receiver = evaluate_expression(env, receiver_expr).await?;
-                match receiver.member_access(&method.name) {
+                match receiver.member_access(parsing::unescape_identifier(&method.name)) {
                     Result::Ok(Value::Func(f)) if f.is_property() => f.execute(env, &[]).await,
                     v => v,
                 }
@@ -481,6 +484,12 @@ pub fn evaluate_expression(env: &mut Env, expr: Box<Expression>) -> BoxFuture<'_
                         let key = kt.cast(&key)?;
                         Ok(values.0.get(&key).cloned().unwrap_or(Value::Null))
                     }
+                    Value::Func(f) => {
+                        let subscript = subscript_opt
+                            .ok_or(anyhow!("functions do not support empty subscript"))?;
+                        let key = evaluate_expression(env, subscript).await?;
+                        f.index(env, &key).await
+                    }
                     Value::TypeObject(type_) => match subscript_opt {
                         Some(subscript) => {
                             let value = evaluate_expression(env, subscript).await?;
@@ -580,7 +589,7 @@ pub fn evaluate_expression(env: &mut Env, expr: Box<Expression>) -> BoxFuture<'_
                     Value::Func(f) => f.execute(env, &args).await,
                     Value::TypeObject(type_) => {
                         if let [arg] = &args[..] {
-                            type_.cast(arg)
+                            type_.cast(&ens::resolve_cast(env, &type_, arg).await?)
                         } else {
                             bail!("cast requires a single argument")
                         }
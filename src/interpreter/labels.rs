@@ -0,0 +1,157 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::PathBuf,
+};
+
+use alloy::primitives::Address;
+use anyhow::{anyhow, bail, Result};
+
+const LABELS_DIR: &str = "eclair/labels";
+
+/// Labels of a chain, used to display addresses in the REPL output and in traces
+#[derive(Debug, Default)]
+pub struct AddressBook {
+    chain_id: u64,
+    addresses: HashMap<String, Address>,
+    labels: HashMap<Address, String>,
+    /// Labels saved in the JSON file of the chain, which is rewritten when labels change
+    saved: BTreeMap<String, Address>,
+}
+
+fn labels_dir() -> Result<PathBuf> {
+    foundry_config::Config::foundry_dir()
+        .map(|dir| dir.join(LABELS_DIR))
+        .ok_or(anyhow!("could not find home directory"))
+}
+
+fn read_labels(chain_id: u64, extension: &str) -> Result<BTreeMap<String, Address>> {
+    let path = labels_dir()?.join(format!("{}.{}", chain_id, extension));
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = fs::read_to_string(&path)?;
+    let labels = match extension {
+        "toml" => toml::from_str(&content)?,
+        _ => serde_json::from_str(&content)?,
+    };
+    Ok(labels)
+}
+
+fn validate_label(label: &str) -> Result<()> {
+    let mut chars = label.chars();
+    let is_valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !is_valid {
+        bail!("invalid label {}, labels must be valid identifiers", label)
+    }
+    Ok(())
+}
+
+impl AddressBook {
+    /// Loads the labels of the given chain from `<chain_id>.json` and `<chain_id>.toml`
+    /// in the labels directory
+    pub fn load(chain_id: u64) -> Result<Self> {
+        let saved = read_labels(chain_id, "json")?;
+        let mut book = AddressBook {
+            chain_id,
+            ..Default::default()
+        };
+        for (label, addr) in read_labels(chain_id, "toml")?.iter().chain(saved.iter()) {
+            validate_label(label)?;
+            book.insert(label, *addr);
+        }
+        book.saved = saved;
+        Ok(book)
+    }
+
+    /// Labels an address, replacing any previous label of the address,
+    /// and saves it to the JSON file of the chain
+    pub fn set(&mut self, addr: Address, label: &str) -> Result<()> {
+        validate_label(label)?;
+        if let Some(previous) = self.labels.get(&addr).cloned() {
+            self.saved.remove(&previous);
+        }
+        self.insert(label, addr);
+        self.saved.insert(label.to_string(), addr);
+        self.save()
+    }
+
+    pub fn remove(&mut self, label: &str) -> Result<()> {
+        if self.unlink(label).is_none() {
+            bail!("no address labelled {}", label)
+        }
+        if self.saved.remove(label).is_some() {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    pub fn get_address(&self, label: &str) -> Option<Address> {
+        self.addresses.get(label).copied()
+    }
+
+    pub fn get_label(&self, addr: &Address) -> Option<&String> {
+        self.labels.get(addr)
+    }
+
+    pub fn list(&self) -> Vec<(String, Address)> {
+        let mut labels: Vec<_> = self
+            .addresses
+            .iter()
+            .map(|(k, v)| (k.clone(), *v))
+            .collect();
+        labels.sort();
+        labels
+    }
+
+    fn insert(&mut self, label: &str, addr: Address) {
+        self.unlink(label);
+        if let Some(previous) = self.labels.insert(addr, label.to_string()) {
+            self.addresses.remove(&previous);
+        }
+        self.addresses.insert(label.to_string(), addr);
+    }
+
+    fn unlink(&mut self, label: &str) -> Option<Address> {
+        let addr = self.addresses.remove(label)?;
+        self.labels.remove(&addr);
+        Some(addr)
+    }
+
+    fn save(&self) -> Result<()> {
+        let dir = labels_dir()?;
+        fs::create_dir_all(&dir)?;
+        let content = serde_json::to_string_pretty(&self.saved)?;
+        fs::write(dir.join(format!("{}.json", self.chain_id)), content)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_relabels_address() {
+        let mut book = AddressBook::default();
+        let addr = Address::repeat_byte(1);
+        book.insert("A", addr);
+        book.insert("B", addr);
+        assert_eq!(book.get_address("A"), None);
+        assert_eq!(book.get_label(&addr).map(String::as_str), Some("B"));
+        assert_eq!(book.unlink("B"), Some(addr));
+        assert!(book.list().is_empty());
+    }
+
+    #[test]
+    fn test_validate_label() {
+        assert!(validate_label("Treasury").is_ok());
+        assert!(validate_label("_multisig2").is_ok());
+        assert!(validate_label("2fa").is_err());
+        assert!(validate_label("my-safe").is_err());
+        assert!(validate_label("").is_err());
+    }
+}
@@ -1,18 +1,28 @@
 mod assignment;
 mod builtins;
+mod cache;
 mod config;
+mod ens;
 mod env;
+mod fees;
 mod functions;
 #[allow(clippy::module_inception)]
 mod interpreter;
+mod labels;
 mod parsing;
+mod proxy;
+mod recording;
+mod remote_signer;
+mod storage;
 pub mod tracing;
+mod transport;
 mod types;
 mod utils;
 mod value;
 
 pub use config::Config;
-pub use env::Env;
+pub use env::{EclairProvider, Env};
 pub use interpreter::*;
+pub use recording::{RecordLayer, Recorder};
 pub use types::{ContractInfo, Type};
 pub use value::Value;
@@ -2,6 +2,19 @@ use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use solang_parser::pt::{ContractDefinition, ContractPart, SourceUnitPart, Statement};
 
+/// Name given to the `storage` keyword when it is used as a value,
+/// as solang does not accept keywords as identifiers
+const ESCAPED_STORAGE: &str = "__storage";
+
+/// Returns the name of an identifier as written in the REPL
+pub fn unescape_identifier(name: &str) -> &str {
+    if name == ESCAPED_STORAGE {
+        "storage"
+    } else {
+        name
+    }
+}
+
 fn wrap_contract(function: &str) -> String {
     format!(
         r#"contract ReplContract {{
@@ -34,7 +47,7 @@ pub enum ParsedCode {
 }
 
 fn parse_code(code: &str) -> Result<ContractDefinition> {
-    match solang_parser::parse(code, 0) {
+    match solang_parser::parse(&preprocess_code(code), 0) {
         Ok((tree, _comments)) => match &tree.0[0] {
             SourceUnitPart::ContractDefinition(def) => Ok(*def.clone()),
             _ => Err(anyhow!("parse error: {}", code)),
@@ -71,3 +84,109 @@ pub fn parse_input(input: &str) -> Result<ParsedCode> {
             .map_err(|_| e),
     }
 }
+
+/// Replaces `@Label` address literals with a lookup in the address book
+/// and escapes the `storage` keyword when it is used as a value, e.g. in `vault.storage.x`,
+/// leaving strings and comments untouched
+fn preprocess_code(code: &str) -> String {
+    let mut result = String::with_capacity(code.len());
+    let mut chars = code.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                result.push(c);
+                while let Some(next) = chars.next() {
+                    result.push(next);
+                    if next == '\\' {
+                        result.extend(chars.next());
+                    } else if next == c {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                result.push(c);
+                for next in chars.by_ref() {
+                    result.push(next);
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                result.push(c);
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    result.push(next);
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            '@' if chars
+                .peek()
+                .is_some_and(|n| n.is_ascii_alphabetic() || *n == '_') =>
+            {
+                let mut label = String::new();
+                while let Some(next) = chars.next_if(|n| n.is_ascii_alphanumeric() || *n == '_') {
+                    label.push(next);
+                }
+                result.push_str(&format!("labels.get(\"{}\")", label));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut identifier = c.to_string();
+                while let Some(next) = chars.next_if(|n| n.is_ascii_alphanumeric() || *n == '_') {
+                    identifier.push(next);
+                }
+                if identifier == "storage" && (result.ends_with('.') || chars.peek() == Some(&'.'))
+                {
+                    identifier = ESCAPED_STORAGE.to_string();
+                }
+                result.push_str(&identifier);
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preprocess_label_literals() {
+        assert_eq!(
+            preprocess_code("@Treasury.balance"),
+            "labels.get(\"Treasury\").balance"
+        );
+        assert_eq!(
+            preprocess_code("token.transfer(@my_safe, 1)"),
+            "token.transfer(labels.get(\"my_safe\"), 1)"
+        );
+        assert_eq!(preprocess_code("\"@Treasury\""), "\"@Treasury\"");
+        assert_eq!(
+            preprocess_code("1 // @Treasury\n@A"),
+            "1 // @Treasury\nlabels.get(\"A\")"
+        );
+        assert_eq!(preprocess_code("/* @A */ 1"), "/* @A */ 1");
+    }
+
+    #[test]
+    fn test_preprocess_storage_keyword() {
+        assert_eq!(
+            preprocess_code("vault.storage.totalShares"),
+            "vault.__storage.totalShares"
+        );
+        assert_eq!(
+            preprocess_code("storage.erc7201(\"storage\")"),
+            "__storage.erc7201(\"storage\")"
+        );
+        assert_eq!(
+            preprocess_code("function f(uint[] storage xs) {}"),
+            "function f(uint[] storage xs) {}"
+        );
+        assert_eq!(preprocess_code("storage_slot.x"), "storage_slot.x");
+    }
+}
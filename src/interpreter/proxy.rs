@@ -0,0 +1,174 @@
+use alloy::{
+    json_abi::JsonAbi,
+    primitives::{b256, Address, B256, U256},
+    providers::Provider,
+    sol,
+};
+use anyhow::Result;
+
+use super::Env;
+
+/// `bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)`
+const EIP1967_IMPLEMENTATION_SLOT: B256 =
+    b256!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+/// `bytes32(uint256(keccak256("eip1967.proxy.beacon")) - 1)`
+const EIP1967_BEACON_SLOT: B256 =
+    b256!("a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50");
+/// `bytes32(uint256(keccak256("eip1967.proxy.admin")) - 1)`
+const EIP1967_ADMIN_SLOT: B256 =
+    b256!("b53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103");
+/// `keccak256("PROXIABLE")`
+const EIP1822_PROXIABLE_SLOT: B256 =
+    b256!("c5f16f0fcc639fa48a6947836d9850f504798523bf8c9a3a87d5876cf622bcf7");
+
+sol! {
+    #[sol(rpc)]
+    interface Beacon {
+        function implementation() external view returns (address);
+    }
+
+    #[sol(rpc)]
+    interface SafeProxy {
+        function masterCopy() external view returns (address);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProxyKind {
+    Eip1967,
+    Beacon,
+    Eip1822,
+    Safe,
+}
+
+impl std::fmt::Display for ProxyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProxyKind::Eip1967 => write!(f, "EIP-1967"),
+            ProxyKind::Beacon => write!(f, "Beacon"),
+            ProxyKind::Eip1822 => write!(f, "EIP-1822"),
+            ProxyKind::Safe => write!(f, "Safe"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Proxy {
+    pub kind: ProxyKind,
+    pub implementation: Address,
+    pub beacon: Option<Address>,
+    pub admin: Option<Address>,
+}
+
+/// Reads an address stored in a slot, returning None if the slot is empty
+/// or does not contain an address
+async fn read_address_slot(env: &Env, addr: Address, slot: B256) -> Result<Option<Address>> {
+    let value = env
+        .get_provider()
+        .get_storage_at(addr, U256::from_be_bytes(slot.0))
+        .block_id(env.block())
+        .await?;
+    if value.is_zero() || value >= U256::from(1) << 160 {
+        return Ok(None);
+    }
+    Ok(Some(Address::from_word(B256::from(
+        value.to_be_bytes::<32>(),
+    ))))
+}
+
+async fn get_safe_singleton(env: &Env, addr: Address) -> Result<Option<Address>> {
+    let singleton = match read_address_slot(env, addr, B256::ZERO).await? {
+        Some(singleton) => singleton,
+        None => return Ok(None),
+    };
+    // slot 0 is used by most contracts, so check that the proxy exposes the same singleton
+    let safe = SafeProxy::new(addr, env.get_provider());
+    match safe.masterCopy().block(env.block()).call().await {
+        Ok(master_copy) if master_copy == singleton => Ok(Some(singleton)),
+        _ => Ok(None),
+    }
+}
+
+/// Detects EIP-1967, beacon, EIP-1822 and Safe proxies, in this order,
+/// returning None if the address is not a proxy
+pub async fn get_proxy(env: &Env, addr: Address) -> Result<Option<Proxy>> {
+    let admin = read_address_slot(env, addr, EIP1967_ADMIN_SLOT).await?;
+    let proxy = |kind, implementation, beacon| {
+        Some(Proxy {
+            kind,
+            implementation,
+            beacon,
+            admin,
+        })
+    };
+
+    if let Some(implementation) = read_address_slot(env, addr, EIP1967_IMPLEMENTATION_SLOT).await? {
+        return Ok(proxy(ProxyKind::Eip1967, implementation, None));
+    }
+    if let Some(beacon) = read_address_slot(env, addr, EIP1967_BEACON_SLOT).await? {
+        let implementation = Beacon::new(beacon, env.get_provider())
+            .implementation()
+            .block(env.block())
+            .call()
+            .await?;
+        return Ok(proxy(ProxyKind::Beacon, implementation, Some(beacon)));
+    }
+    if let Some(implementation) = read_address_slot(env, addr, EIP1822_PROXIABLE_SLOT).await? {
+        return Ok(proxy(ProxyKind::Eip1822, implementation, None));
+    }
+    if let Some(singleton) = get_safe_singleton(env, addr).await? {
+        return Ok(proxy(ProxyKind::Safe, singleton, None));
+    }
+    Ok(None)
+}
+
+/// Adds the functions, events and errors of the proxy missing from the implementation ABI
+pub fn merge_abis(implementation: JsonAbi, proxy: &JsonAbi) -> JsonAbi {
+    let mut abi = implementation;
+    for function in proxy.functions() {
+        let overloads = abi.functions.entry(function.name.clone()).or_default();
+        if !overloads
+            .iter()
+            .any(|f| f.selector() == function.selector())
+        {
+            overloads.push(function.clone());
+        }
+    }
+    for event in proxy.events() {
+        let overloads = abi.events.entry(event.name.clone()).or_default();
+        if !overloads.iter().any(|e| e.selector() == event.selector()) {
+            overloads.push(event.clone());
+        }
+    }
+    for error in proxy.errors() {
+        let overloads = abi.errors.entry(error.name.clone()).or_default();
+        if !overloads.iter().any(|e| e.selector() == error.selector()) {
+            overloads.push(error.clone());
+        }
+    }
+    abi
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_abis() {
+        let implementation = JsonAbi::parse([
+            "function transfer(address to, uint256 amount) returns (bool)",
+            "event Transfer(address indexed from, address indexed to, uint256 value)",
+        ])
+        .unwrap();
+        let proxy = JsonAbi::parse([
+            "function upgradeTo(address implementation)",
+            "function transfer(address to, uint256 amount) returns (bool)",
+            "event Upgraded(address indexed implementation)",
+        ])
+        .unwrap();
+        let merged = merge_abis(implementation, &proxy);
+        assert_eq!(merged.functions().count(), 2);
+        assert_eq!(merged.events().count(), 2);
+        assert!(merged.function("upgradeTo").is_some());
+    }
+}
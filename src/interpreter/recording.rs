@@ -0,0 +1,268 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{self, File},
+    io::{Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use alloy::{
+    rpc::json_rpc::{RequestPacket, Response, ResponsePacket, SerializedRequest},
+    transports::{TransportError, TransportErrorKind, TransportFut},
+};
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Value as JsonValue};
+use tower::{Layer, Service};
+
+fn request_key(request: &SerializedRequest) -> (String, JsonValue) {
+    let params = request
+        .params()
+        .and_then(|p| serde_json::from_str(p.get()).ok())
+        .unwrap_or(JsonValue::Null);
+    (request.method().to_string(), params)
+}
+
+/// Records the JSON-RPC requests sent and their responses to a file.
+/// Each call is appended before the closing bracket of the JSON array,
+/// so that the file is complete even if the session is interrupted
+#[derive(Debug)]
+pub struct Recorder {
+    path: PathBuf,
+    /// Recording file, created when the first call is recorded
+    file: Mutex<Option<File>>,
+}
+
+impl Recorder {
+    pub fn new(path: PathBuf) -> Self {
+        Recorder {
+            path,
+            file: Mutex::new(None),
+        }
+    }
+
+    fn record(&self, request: &SerializedRequest, response: &Response) -> Result<()> {
+        let (method, params) = request_key(request);
+        let call = serde_json::to_string(&json!({
+            "method": method,
+            "params": params,
+            "response": serde_json::to_value(response)?,
+        }))?;
+        let mut recording = self.file.lock().unwrap();
+        match recording.as_mut() {
+            Some(file) => {
+                // overwrite the closing "\n]" of the previous call
+                file.seek(SeekFrom::End(-2))?;
+                file.write_all(format!(",\n{}\n]", call).as_bytes())?;
+            }
+            None => {
+                let mut file = File::create(&self.path)?;
+                file.write_all(format!("[\n{}\n]", call).as_bytes())?;
+                *recording = Some(file);
+            }
+        }
+        Ok(())
+    }
+
+    fn record_packet(&self, request: &RequestPacket, response: &ResponsePacket) -> Result<()> {
+        let requests = match request {
+            RequestPacket::Single(req) => std::slice::from_ref(req),
+            RequestPacket::Batch(reqs) => reqs.as_slice(),
+        };
+        let responses = match response {
+            ResponsePacket::Single(res) => std::slice::from_ref(res),
+            ResponsePacket::Batch(res) => res.as_slice(),
+        };
+        for req in requests {
+            let res = responses
+                .iter()
+                .find(|res| &res.id == req.id())
+                .ok_or(anyhow!("missing response for {}", req.method()))?;
+            self.record(req, res)?;
+        }
+        Ok(())
+    }
+}
+
+/// Layer recording all the requests going through it
+#[derive(Debug, Clone)]
+pub struct RecordLayer {
+    recorder: Option<Arc<Recorder>>,
+}
+
+impl RecordLayer {
+    pub fn new(recorder: Option<Arc<Recorder>>) -> Self {
+        RecordLayer { recorder }
+    }
+}
+
+impl<S> Layer<S> for RecordLayer {
+    type Service = RecordService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RecordService {
+            inner,
+            recorder: self.recorder.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordService<S> {
+    inner: S,
+    recorder: Option<Arc<Recorder>>,
+}
+
+impl<S> Service<RequestPacket> for RecordService<S>
+where
+    S: Service<RequestPacket, Response = ResponsePacket, Error = TransportError>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let mut inner = self.inner.clone();
+        let recorder = self.recorder.clone();
+
+        Box::pin(async move {
+            let recorder = match recorder {
+                Some(recorder) => recorder,
+                None => return inner.call(request).await,
+            };
+            let response = inner.call(request.clone()).await?;
+            if let Err(e) = recorder.record_packet(&request, &response) {
+                eprintln!("warning: failed to record RPC call: {}", e);
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// Transport serving responses from a file written by a [`Recorder`] without any network access.
+/// Identical requests are answered in the order in which they were recorded,
+/// the last response being reused once all of them have been served
+#[derive(Debug, Clone)]
+pub struct ReplayTransport {
+    responses: Arc<Mutex<HashMap<(String, String), VecDeque<String>>>>,
+}
+
+impl ReplayTransport {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("failed to read recording {}: {}", path.display(), e))?;
+        let calls: Vec<JsonValue> = serde_json::from_str(&content)?;
+        let mut responses: HashMap<_, VecDeque<_>> = HashMap::new();
+        for call in calls {
+            let (method, params, response) = match (
+                call.get("method").and_then(|m| m.as_str()),
+                call.get("params"),
+                call.get("response"),
+            ) {
+                (Some(method), Some(params), Some(response)) => (method, params, response),
+                _ => bail!("invalid call in recording {}: {}", path.display(), call),
+            };
+            responses
+                .entry((method.to_string(), params.to_string()))
+                .or_default()
+                .push_back(response.to_string());
+        }
+        Ok(ReplayTransport {
+            responses: Arc::new(Mutex::new(responses)),
+        })
+    }
+
+    fn replay(&self, request: &SerializedRequest) -> Result<Response, TransportError> {
+        let (method, params) = request_key(request);
+        let mut responses = self.responses.lock().unwrap();
+        let recorded = responses
+            .get_mut(&(method.clone(), params.to_string()))
+            .ok_or_else(|| {
+                TransportErrorKind::custom_str(&format!(
+                    "no recorded response for {} with params {}",
+                    method, params
+                ))
+            })?;
+        let raw = if recorded.len() > 1 {
+            recorded.pop_front().unwrap()
+        } else {
+            recorded[0].clone()
+        };
+        let mut response: Response =
+            serde_json::from_str(&raw).map_err(|e| TransportError::deser_err(e, &raw))?;
+        response.id = request.id().clone();
+        Ok(response)
+    }
+}
+
+impl Service<RequestPacket> for ReplayTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let result = match &request {
+            RequestPacket::Single(req) => self.replay(req).map(ResponsePacket::Single),
+            RequestPacket::Batch(reqs) => reqs
+                .iter()
+                .map(|req| self.replay(req))
+                .collect::<Result<Vec<_>, _>>()
+                .map(ResponsePacket::Batch),
+        };
+        Box::pin(async move { result })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::rpc::json_rpc::{Id, Request, ResponsePayload};
+    use serde_json::value::RawValue;
+
+    use super::*;
+
+    fn balance_request() -> SerializedRequest {
+        let params = ("0x0000000000000000000000000000000000000001", "latest");
+        Request::new("eth_getBalance", Id::Number(1), params)
+            .serialize()
+            .unwrap()
+    }
+
+    fn balance_response(balance: &str) -> Response {
+        Response {
+            id: Id::Number(1),
+            payload: ResponsePayload::Success(RawValue::from_string(balance.to_string()).unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let path =
+            std::env::temp_dir().join(format!("eclair-recording-{}.json", std::process::id()));
+        let recorder = Recorder::new(path.clone());
+        recorder
+            .record(&balance_request(), &balance_response("\"0x1\""))
+            .unwrap();
+        recorder
+            .record(&balance_request(), &balance_response("\"0x2\""))
+            .unwrap();
+
+        let replay = ReplayTransport::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        for expected in ["\"0x1\"", "\"0x2\"", "\"0x2\""] {
+            let response = replay.replay(&balance_request()).unwrap();
+            assert_eq!(response.payload.as_success().unwrap().get(), expected);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use alloy::{
+    consensus::{SignableTransaction, Transaction, TxEnvelope},
+    eips::{eip7702::Authorization, Decodable2718, Typed2718},
+    network::TxSigner,
+    primitives::{Address, Bytes, ChainId, Signature, B256},
+    rpc::{
+        client::{ClientBuilder, RpcClient},
+        json_rpc::{RpcRecv, RpcSend},
+        types::TransactionRequest,
+    },
+    signers::{Error, Result, Signer},
+};
+use async_trait::async_trait;
+use url::Url;
+
+use super::env::WalletSigner;
+
+/// Signer forwarding signing requests to an external JSON-RPC endpoint,
+/// such as a remote signer service or a node holding the key
+#[derive(Clone, Debug)]
+pub struct RemoteSigner {
+    client: RpcClient,
+    address: Address,
+    chain_id: Option<ChainId>,
+}
+
+impl RemoteSigner {
+    pub fn new(url: Url, address: Address) -> Self {
+        Self {
+            client: ClientBuilder::default().http(url),
+            address,
+            chain_id: None,
+        }
+    }
+
+    async fn request<P: RpcSend, R: RpcRecv>(&self, method: &'static str, params: P) -> Result<R> {
+        self.client
+            .request(method, params)
+            .await
+            .map_err(Error::other)
+    }
+
+    fn transaction_request(&self, tx: &dyn SignableTransaction<Signature>) -> TransactionRequest {
+        TransactionRequest {
+            from: Some(self.address),
+            to: Some(tx.kind()),
+            gas: Some(tx.gas_limit()),
+            gas_price: tx.gas_price(),
+            max_fee_per_gas: tx.is_dynamic_fee().then(|| tx.max_fee_per_gas()),
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas(),
+            value: Some(tx.value()),
+            input: tx.input().clone().into(),
+            nonce: Some(tx.nonce()),
+            chain_id: tx.chain_id(),
+            access_list: tx.access_list().cloned(),
+            authorization_list: tx.authorization_list().map(|list| list.to_vec()),
+            transaction_type: Some(tx.ty()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Parses the signed transaction returned by `eth_signTransaction`,
+/// either as raw bytes or as an object with a `raw` field as returned by geth
+fn parse_signed_transaction(response: serde_json::Value) -> Result<TxEnvelope> {
+    let raw = match response.get("raw") {
+        Some(raw) => raw.clone(),
+        None => response,
+    };
+    let raw: Bytes = serde_json::from_value(raw).map_err(Error::other)?;
+    TxEnvelope::decode_2718(&mut raw.as_ref()).map_err(Error::other)
+}
+
+fn parse_signature(response: Bytes) -> Result<Signature> {
+    Signature::try_from(response.as_ref()).map_err(Error::other)
+}
+
+#[async_trait]
+impl TxSigner<Signature> for RemoteSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &mut dyn SignableTransaction<Signature>,
+    ) -> Result<Signature> {
+        if let Some(chain_id) = self.chain_id {
+            if !tx.set_chain_id_checked(chain_id) {
+                return Err(Error::TransactionChainIdMismatch {
+                    signer: chain_id,
+                    tx: tx.chain_id().unwrap(),
+                });
+            }
+        }
+        let request = self.transaction_request(tx);
+        let response = self.request("eth_signTransaction", (request,)).await?;
+        let envelope = parse_signed_transaction(response)?;
+        if envelope.signature_hash() != tx.signature_hash() {
+            return Err(Error::other("remote signer signed a different transaction"));
+        }
+        Ok(*envelope.signature())
+    }
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    async fn sign_hash(&self, _hash: &B256) -> Result<Signature> {
+        Err(Error::UnsupportedOperation(
+            alloy::signers::UnsupportedSignerOperation::SignHash,
+        ))
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        let params = (self.address, Bytes::copy_from_slice(message));
+        parse_signature(self.request("eth_sign", params).await?)
+    }
+
+    async fn sign_dynamic_typed_data(
+        &self,
+        payload: &alloy::dyn_abi::TypedData,
+    ) -> Result<Signature> {
+        let params = (self.address, payload.clone());
+        parse_signature(self.request("eth_signTypedData_v4", params).await?)
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> Option<ChainId> {
+        self.chain_id
+    }
+
+    fn set_chain_id(&mut self, chain_id: Option<ChainId>) {
+        self.chain_id = chain_id;
+    }
+}
+
+#[async_trait]
+impl WalletSigner for RemoteSigner {
+    async fn sign_authorization(
+        &self,
+        _authorization: &Authorization,
+    ) -> anyhow::Result<Signature> {
+        anyhow::bail!("remote signers do not support signing authorizations")
+    }
+}
+
+alloy::network::impl_into_wallet!(RemoteSigner);
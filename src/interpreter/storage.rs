@@ -0,0 +1,670 @@
+use std::sync::Arc;
+
+use alloy::{
+    dyn_abi::{DynSolType, DynSolValue},
+    eips::BlockId,
+    primitives::{keccak256, Address, B256, I256, U256},
+    providers::Provider,
+};
+use anyhow::{anyhow, bail, Result};
+use futures::{future::BoxFuture, FutureExt};
+use indexmap::IndexMap;
+
+use crate::loaders::types::{StorageEntry, StorageLayout, StorageType};
+
+use super::{
+    functions::{Function, FunctionDef, FunctionParam},
+    types::HashableIndexMap,
+    EclairProvider, Env, Type, Value,
+};
+
+/// Dynamic arrays longer than this are not read at once but only support indexing
+const MAX_READ_LENGTH: usize = 256;
+
+/// Reads the storage of a contract, on the chain of the contract if it has one
+struct StorageReader {
+    provider: EclairProvider,
+    address: Address,
+    block: BlockId,
+}
+
+impl StorageReader {
+    async fn new(env: &mut Env, contract: &Value) -> Result<Self> {
+        let (_, address, chain) = contract.as_contract()?;
+        let provider = env.get_chain_provider(chain.as_deref()).await?;
+        // the block set with vm.block only applies to the current chain
+        let block = if chain.is_some() {
+            BlockId::latest()
+        } else {
+            env.block()
+        };
+        Ok(StorageReader {
+            provider,
+            address,
+            block,
+        })
+    }
+
+    async fn read_slot(&self, slot: U256) -> Result<U256> {
+        let value = self
+            .provider
+            .get_storage_at(self.address, slot)
+            .block_id(self.block)
+            .await?;
+        Ok(value)
+    }
+
+    /// Reads a `bytes` or `string` value, stored inline when shorter than 32 bytes
+    async fn read_bytes(&self, slot: U256) -> Result<Vec<u8>> {
+        let word = self.read_slot(slot).await?;
+        if !word.bit(0) {
+            let length = (word & U256::from(0xff)).to::<usize>() / 2;
+            return Ok(word.to_be_bytes::<32>()[..length].to_vec());
+        }
+        let length = as_length((word - U256::from(1)) / U256::from(2))?;
+        let start = hash_slot(slot);
+        let mut data = Vec::with_capacity(length);
+        for i in 0..length.div_ceil(32) {
+            let word = self.read_slot(start + U256::from(i)).await?;
+            data.extend_from_slice(&word.to_be_bytes::<32>());
+        }
+        data.truncate(length);
+        Ok(data)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Location {
+    slot: U256,
+    offset: usize,
+}
+
+fn as_length(value: U256) -> Result<usize> {
+    if value > U256::from(u32::MAX) {
+        bail!("invalid length {}", value)
+    }
+    Ok(value.to::<usize>())
+}
+
+fn parse_slot(slot: &str) -> Result<U256> {
+    U256::from_str_radix(slot, 10).map_err(|e| anyhow!("invalid slot {}: {}", slot, e))
+}
+
+/// Returns the slot where the data of dynamic arrays and long bytes starts
+fn hash_slot(slot: U256) -> U256 {
+    U256::from_be_bytes(keccak256(slot.to_be_bytes::<32>()).0)
+}
+
+fn get_type<'a>(layout: &'a StorageLayout, type_id: &str) -> Result<&'a StorageType> {
+    layout
+        .types
+        .get(type_id)
+        .ok_or(anyhow!("unknown storage type {}", type_id))
+}
+
+fn contains_mapping(layout: &StorageLayout, type_id: &str) -> bool {
+    let type_ = match layout.types.get(type_id) {
+        Some(type_) => type_,
+        None => return false,
+    };
+    type_.encoding == "mapping"
+        || type_
+            .base
+            .as_ref()
+            .is_some_and(|base| contains_mapping(layout, base))
+        || type_
+            .members
+            .iter()
+            .flatten()
+            .any(|member| contains_mapping(layout, &member.type_id))
+}
+
+/// Returns the Solidity type of value types and mapping keys
+fn solidity_type(type_: &StorageType) -> Type {
+    let label = type_.label.as_str();
+    if label.starts_with("contract ") || label.starts_with("address") {
+        Type::Address
+    } else if label.starts_with("enum ") {
+        Type::Uint(8)
+    } else {
+        // user-defined value types are decoded as their underlying integer
+        DynSolType::parse(label)
+            .map(Type::from)
+            .unwrap_or(Type::Uint(type_.number_of_bytes * 8))
+    }
+}
+
+fn struct_name(type_: &StorageType) -> String {
+    let name = type_.label.trim_start_matches("struct ");
+    name.rsplit('.').next().unwrap_or(name).to_string()
+}
+
+/// Returns the length of a static array from its label, e.g. `uint256[3]`
+fn static_array_length(type_: &StorageType) -> Result<usize> {
+    type_
+        .label
+        .rsplit_once('[')
+        .and_then(|(_, length)| length.trim_end_matches(']').parse().ok())
+        .ok_or(anyhow!("invalid array type {}", type_.label))
+}
+
+/// Encodes a mapping key as it is hashed to compute the slot of its value:
+/// strings and bytes are used as is while value types are padded to 32 bytes
+fn encode_mapping_key(key: &Value) -> Result<Vec<u8>> {
+    match key {
+        Value::Str(s) => Ok(s.as_bytes().to_vec()),
+        Value::Bytes(b) => Ok(b.clone()),
+        Value::Tuple(_) | Value::NamedTuple(..) | Value::Array(..) | Value::Mapping(..) => {
+            bail!("{} cannot be used as a mapping key", key.get_type())
+        }
+        key => Ok(DynSolValue::try_from(key)?.abi_encode()),
+    }
+}
+
+/// Computes the slot of a mapping value, which is `keccak256(key . slot)`
+pub fn mapping_slot(key: &Value, slot: U256) -> Result<U256> {
+    let key = encode_mapping_key(key)?;
+    let hash = keccak256([key.as_slice(), &slot.to_be_bytes::<32>()].concat());
+    Ok(U256::from_be_bytes(hash.0))
+}
+
+/// Computes the slot of a mapping value, converting the key to the key type of the mapping
+fn typed_mapping_slot(key_type: &StorageType, key: &Value, slot: U256) -> Result<U256> {
+    mapping_slot(&solidity_type(key_type).cast(key)?, slot)
+}
+
+/// Computes the slot of an element of a dynamic array stored at the given slot
+pub fn array_slot(slot: U256, index: usize, element_size: usize) -> U256 {
+    element_location(hash_slot(slot), element_size, index).slot
+}
+
+/// Computes the slot of an ERC-7201 namespace,
+/// which is `keccak256(abi.encode(uint256(keccak256(id)) - 1)) & ~bytes32(uint256(0xff))`
+pub fn erc7201_slot(id: &str) -> U256 {
+    let id_hash = U256::from_be_bytes(keccak256(id.as_bytes()).0);
+    hash_slot(id_hash - U256::from(1)) & !U256::from(0xff)
+}
+
+/// Extracts `size` bytes starting `offset` bytes from the right of a slot
+pub fn unpack(word: U256, offset: usize, size: usize) -> U256 {
+    let mask = if size >= 32 {
+        U256::MAX
+    } else {
+        (U256::from(1) << (size * 8)) - U256::from(1)
+    };
+    (word >> (offset * 8)) & mask
+}
+
+/// Computes the location of an array element, elements smaller than a slot being packed together
+fn element_location(start: U256, element_size: usize, index: usize) -> Location {
+    if element_size >= 32 {
+        let slots = element_size.div_ceil(32);
+        return Location {
+            slot: start + U256::from(index * slots),
+            offset: 0,
+        };
+    }
+    let per_slot = 32 / element_size;
+    Location {
+        slot: start + U256::from(index / per_slot),
+        offset: (index % per_slot) * element_size,
+    }
+}
+
+/// Decodes a value type stored at the given offset of a slot
+fn decode_word(type_: &StorageType, word: U256, offset: usize) -> Result<Value> {
+    let size = type_.number_of_bytes;
+    let raw = unpack(word, offset, size);
+    let value = match solidity_type(type_) {
+        Type::Bool => Value::Bool(!raw.is_zero()),
+        Type::Address => Value::Addr(Address::from_word(B256::from(raw.to_be_bytes::<32>()))),
+        Type::Uint(bits) => Value::Uint(raw, bits),
+        Type::Int(bits) => {
+            let is_negative = size < 32 && raw.bit(size * 8 - 1);
+            let value = if is_negative {
+                raw | (U256::MAX << (size * 8))
+            } else {
+                raw
+            };
+            Value::Int(I256::from_raw(value), bits)
+        }
+        Type::FixBytes(n) => {
+            let mut bytes = B256::ZERO;
+            bytes[..n].copy_from_slice(&raw.to_be_bytes::<32>()[32 - n..]);
+            Value::FixBytes(bytes, n)
+        }
+        t => bail!("cannot decode {} from storage", t),
+    };
+    Ok(value)
+}
+
+/// Storage variable of a contract, or part of one, such as a struct member or mapping value.
+/// Variables are read when accessed, except for the ones containing mappings,
+/// which can only be accessed through their members or keys
+#[derive(Debug, Clone)]
+pub struct StorageVariable {
+    name: String,
+    layout: Arc<StorageLayout>,
+    type_id: String,
+    location: Location,
+    /// Length of dynamic arrays, once read
+    length: Option<usize>,
+}
+
+impl StorageVariable {
+    fn new(
+        layout: Arc<StorageLayout>,
+        name: String,
+        entry: &StorageEntry,
+        base: U256,
+    ) -> Result<Self> {
+        Ok(StorageVariable {
+            name,
+            layout,
+            type_id: entry.type_id.clone(),
+            location: Location {
+                slot: base + parse_slot(&entry.slot)?,
+                offset: entry.offset,
+            },
+            length: None,
+        })
+    }
+
+    fn child(&self, name: String, type_id: &str, location: Location) -> Self {
+        StorageVariable {
+            name,
+            layout: self.layout.clone(),
+            type_id: type_id.to_string(),
+            location,
+            length: None,
+        }
+    }
+
+    fn get_type(&self) -> Result<&StorageType> {
+        get_type(&self.layout, &self.type_id)
+    }
+
+    fn to_value(&self, contract: &Value) -> Value {
+        Function::new(Arc::new(self.clone()), Some(contract)).into()
+    }
+
+    fn member(&self, name: &str) -> Result<Option<Self>> {
+        let members = match &self.get_type()?.members {
+            Some(members) => members,
+            None => return Ok(None),
+        };
+        let member = match members.iter().find(|m| m.label == name) {
+            Some(member) => member,
+            None => return Ok(None),
+        };
+        let name = format!("{}.{}", self.name, name);
+        StorageVariable::new(self.layout.clone(), name, member, self.location.slot).map(Some)
+    }
+
+    fn element(&self, length: usize, index: usize, start: U256) -> Result<Self> {
+        if index >= length {
+            bail!("index {} out of bounds for {}", index, self.name)
+        }
+        let base = self
+            .get_type()?
+            .base
+            .clone()
+            .ok_or(anyhow!("{} is not an array", self.name))?;
+        let element_size = get_type(&self.layout, &base)?.number_of_bytes;
+        let location = element_location(start, element_size, index);
+        Ok(self.child(format!("{}[{}]", self.name, index), &base, location))
+    }
+
+    /// Returns the length of an array and the slot where its elements start
+    async fn array_bounds(&self, reader: &StorageReader) -> Result<(usize, U256)> {
+        let type_ = self.get_type()?;
+        if type_.encoding != "dynamic_array" {
+            return Ok((static_array_length(type_)?, self.location.slot));
+        }
+        let length = match self.length {
+            Some(length) => length,
+            None => as_length(reader.read_slot(self.location.slot).await?)?,
+        };
+        Ok((length, hash_slot(self.location.slot)))
+    }
+
+    fn read<'a>(
+        &'a self,
+        reader: &'a StorageReader,
+        contract: &'a Value,
+    ) -> BoxFuture<'a, Result<Value>> {
+        async move {
+            if contains_mapping(&self.layout, &self.type_id) {
+                return Ok(self.to_value(contract));
+            }
+            let type_ = self.get_type()?;
+            match type_.encoding.as_str() {
+                "bytes" => {
+                    let data = reader.read_bytes(self.location.slot).await?;
+                    if type_.label == "string" {
+                        Ok(Value::Str(String::from_utf8_lossy(&data).to_string()))
+                    } else {
+                        Ok(Value::Bytes(data))
+                    }
+                }
+                "dynamic_array" => {
+                    let mut array = self.clone();
+                    let length = as_length(reader.read_slot(self.location.slot).await?)?;
+                    array.length = Some(length);
+                    if length > MAX_READ_LENGTH {
+                        return Ok(array.to_value(contract));
+                    }
+                    array.read_array(reader, contract).await
+                }
+                _ if type_.members.is_some() => {
+                    let mut fields = IndexMap::new();
+                    for member in type_.members.iter().flatten() {
+                        let variable = self.member(&member.label)?.unwrap();
+                        fields.insert(member.label.clone(), variable.read(reader, contract).await?);
+                    }
+                    Ok(Value::NamedTuple(
+                        struct_name(type_),
+                        HashableIndexMap(fields),
+                    ))
+                }
+                _ if type_.base.is_some() => self.read_array(reader, contract).await,
+                _ => {
+                    let word = reader.read_slot(self.location.slot).await?;
+                    decode_word(type_, word, self.location.offset)
+                }
+            }
+        }
+        .boxed()
+    }
+
+    async fn read_array(&self, reader: &StorageReader, contract: &Value) -> Result<Value> {
+        let (length, start) = self.array_bounds(reader).await?;
+        let mut values = vec![];
+        for i in 0..length {
+            let element = self.element(length, i, start)?;
+            values.push(element.read(reader, contract).await?);
+        }
+        let base = self.get_type()?.base.as_deref().unwrap_or_default();
+        let type_ = match get_type(&self.layout, base) {
+            Ok(base) if base.members.is_none() && base.base.is_none() => solidity_type(base),
+            _ => values.first().map(Value::get_type).unwrap_or(Type::Any),
+        };
+        Ok(Value::Array(values, Box::new(type_)))
+    }
+
+    async fn read_index(
+        &self,
+        reader: &StorageReader,
+        contract: &Value,
+        key: &Value,
+    ) -> Result<Value> {
+        let type_ = self.get_type()?;
+        let variable = if type_.encoding == "mapping" {
+            let (key_type, value_type) = match (&type_.key, &type_.value) {
+                (Some(key_type), Some(value_type)) => (key_type, value_type),
+                _ => bail!("invalid mapping type {}", type_.label),
+            };
+            let key_type = get_type(&self.layout, key_type)?;
+            let slot = typed_mapping_slot(key_type, key, self.location.slot)?;
+            let location = Location { slot, offset: 0 };
+            self.child(format!("{}[{}]", self.name, key), value_type, location)
+        } else if type_.base.is_some() {
+            let (length, start) = self.array_bounds(reader).await?;
+            self.element(length, key.as_usize()?, start)?
+        } else {
+            bail!("{} cannot be indexed", self.name)
+        };
+        variable.read(reader, contract).await
+    }
+}
+
+impl FunctionDef for StorageVariable {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_valid_args(&self, _receiver: &Option<Value>) -> Vec<Vec<FunctionParam>> {
+        vec![vec![]]
+    }
+
+    fn is_property(&self) -> bool {
+        true
+    }
+
+    fn execute<'a>(
+        &'a self,
+        env: &'a mut Env,
+        values: &'a [Value],
+        _options: &'a HashableIndexMap<String, Value>,
+    ) -> BoxFuture<'a, Result<Value>> {
+        async move {
+            let contract = values.first().ok_or(anyhow!("no receiver"))?;
+            let reader = StorageReader::new(env, contract).await?;
+            self.read(&reader, contract).await
+        }
+        .boxed()
+    }
+
+    fn member_access(&self, receiver: &Option<Value>, member: &str) -> Option<Value> {
+        // only dynamic arrays too long to be read at once are accessed as variables
+        if let (Some(length), "length") = (self.length, member) {
+            return Some(length.into());
+        }
+        let variable = self.member(member).ok().flatten()?;
+        Some(variable.to_value(receiver.as_ref()?))
+    }
+
+    fn index<'a>(
+        &'a self,
+        env: &'a mut Env,
+        receiver: &'a Option<Value>,
+        key: &'a Value,
+    ) -> BoxFuture<'a, Result<Value>> {
+        async move {
+            let contract = receiver.as_ref().ok_or(anyhow!("no receiver"))?;
+            let reader = StorageReader::new(env, contract).await?;
+            self.read_index(&reader, contract, key).await
+        }
+        .boxed()
+    }
+}
+
+/// Storage of a contract, giving access to its variables by name.
+/// Calling it, or its `dump` method, reads all the variables except for mappings
+#[derive(Debug, Clone)]
+pub struct ContractStorage {
+    layout: Arc<StorageLayout>,
+    is_dump: bool,
+}
+
+impl ContractStorage {
+    pub fn value(layout: Arc<StorageLayout>, contract: &Value) -> Value {
+        let storage = ContractStorage {
+            layout,
+            is_dump: false,
+        };
+        Function::new(Arc::new(storage), Some(contract)).into()
+    }
+
+    fn variable(&self, name: &str) -> Result<Option<StorageVariable>> {
+        let entry = match self.layout.storage.iter().find(|e| e.label == name) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        let name = format!("storage.{}", name);
+        StorageVariable::new(self.layout.clone(), name, entry, U256::ZERO).map(Some)
+    }
+
+    async fn dump(&self, env: &mut Env, contract: &Value) -> Result<Value> {
+        let reader = StorageReader::new(env, contract).await?;
+        let mut fields = IndexMap::new();
+        for entry in self.layout.storage.iter() {
+            if contains_mapping(&self.layout, &entry.type_id) {
+                continue;
+            }
+            let variable = self.variable(&entry.label)?.unwrap();
+            fields.insert(entry.label.clone(), variable.read(&reader, contract).await?);
+        }
+        Ok(Value::NamedTuple(
+            "Storage".to_string(),
+            HashableIndexMap(fields),
+        ))
+    }
+}
+
+impl FunctionDef for ContractStorage {
+    fn name(&self) -> String {
+        if self.is_dump {
+            "storage.dump".to_string()
+        } else {
+            "storage".to_string()
+        }
+    }
+
+    fn get_valid_args(&self, _receiver: &Option<Value>) -> Vec<Vec<FunctionParam>> {
+        vec![vec![]]
+    }
+
+    fn is_property(&self) -> bool {
+        false
+    }
+
+    fn execute<'a>(
+        &'a self,
+        env: &'a mut Env,
+        values: &'a [Value],
+        _options: &'a HashableIndexMap<String, Value>,
+    ) -> BoxFuture<'a, Result<Value>> {
+        async move {
+            let contract = values.first().ok_or(anyhow!("no receiver"))?;
+            self.dump(env, contract).await
+        }
+        .boxed()
+    }
+
+    fn member_access(&self, receiver: &Option<Value>, member: &str) -> Option<Value> {
+        if self.is_dump {
+            return None;
+        }
+        let contract = receiver.as_ref()?;
+        if let Some(variable) = self.variable(member).ok().flatten() {
+            return Some(variable.to_value(contract));
+        }
+        if member == "dump" {
+            let dump = ContractStorage {
+                layout: self.layout.clone(),
+                is_dump: true,
+            };
+            return Some(Function::new(Arc::new(dump), Some(contract)).into());
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{address, b256};
+
+    use super::*;
+
+    fn value_type(label: &str, number_of_bytes: usize) -> StorageType {
+        StorageType {
+            encoding: "inplace".to_string(),
+            label: label.to_string(),
+            number_of_bytes,
+            key: None,
+            value: None,
+            base: None,
+            members: None,
+        }
+    }
+
+    #[test]
+    fn test_mapping_slot() {
+        let owner = address!("00000000000000000000000000000000000000aa");
+        let slot = typed_mapping_slot(
+            &value_type("address", 20),
+            &Value::Addr(owner),
+            U256::from(2),
+        )
+        .unwrap();
+        let expected = keccak256(
+            [
+                owner.into_word().as_slice(),
+                &U256::from(2).to_be_bytes::<32>(),
+            ]
+            .concat(),
+        );
+        assert_eq!(slot, U256::from_be_bytes(expected.0));
+
+        let slot = mapping_slot(&Value::Str("abc".to_string()), U256::ZERO).unwrap();
+        let expected = keccak256([b"abc".as_slice(), &[0u8; 32]].concat());
+        assert_eq!(slot, U256::from_be_bytes(expected.0));
+    }
+
+    #[test]
+    fn test_erc7201_slot() {
+        assert_eq!(
+            erc7201_slot("openzeppelin.storage.ERC20"),
+            U256::from_be_bytes(
+                b256!("52c63247e1f47db19d5ce0460030c497f067ca4cebf71ba98eeadabe20bace00").0
+            )
+        );
+    }
+
+    #[test]
+    fn test_element_location() {
+        let start = U256::from(10);
+        assert_eq!(
+            element_location(start, 32, 3),
+            Location {
+                slot: U256::from(13),
+                offset: 0
+            }
+        );
+        assert_eq!(
+            element_location(start, 64, 3),
+            Location {
+                slot: U256::from(16),
+                offset: 0
+            }
+        );
+        assert_eq!(
+            element_location(start, 8, 5),
+            Location {
+                slot: U256::from(11),
+                offset: 8
+            }
+        );
+        assert_eq!(
+            element_location(start, 20, 1),
+            Location {
+                slot: U256::from(11),
+                offset: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_word() {
+        // uint128 a = 1; int64 b = -2; bool c = true; packed in a single slot
+        let word =
+            U256::from(1) | ((U256::MAX >> 192) - U256::from(1)) << 128 | U256::from(1) << 192;
+        assert_eq!(
+            decode_word(&value_type("uint128", 16), word, 0).unwrap(),
+            Value::Uint(U256::from(1), 128)
+        );
+        assert_eq!(
+            decode_word(&value_type("int64", 8), word, 16).unwrap(),
+            Value::Int(I256::try_from(-2).unwrap(), 64)
+        );
+        assert_eq!(
+            decode_word(&value_type("bool", 1), word, 24).unwrap(),
+            Value::Bool(true)
+        );
+        let bytes = decode_word(&value_type("bytes2", 2), U256::from(0xabcd), 0).unwrap();
+        let mut expected = B256::ZERO;
+        expected[..2].copy_from_slice(&[0xab, 0xcd]);
+        assert_eq!(bytes, Value::FixBytes(expected, 2));
+    }
+}
@@ -1,13 +1,15 @@
+use std::collections::HashMap;
+
 use alloy::{
     dyn_abi::{FunctionExt, JsonAbiExt},
     json_abi::Function,
-    primitives::{Bytes, FixedBytes},
+    primitives::{Address, Bytes, FixedBytes},
     rpc::types::trace::geth::CallFrame,
 };
 use anyhow::Result;
 use itertools::Itertools;
 
-use crate::interpreter::utils::decode_error;
+use crate::interpreter::{proxy::get_proxy, utils::decode_error};
 
 use super::{Env, Value};
 
@@ -52,20 +54,71 @@ fn get_formatted_function(
             }
         }
     }
-    if let Some(output) = output {
-        format!("{} -> {}", input, output)
-    } else {
-        format!("{}", input)
+    match output {
+        Some(output) if is_error => match decode_error(env, output) {
+            Ok(err) => format!("{} -> {}", input, err),
+            Err(_) => format!("{} -> {}", input, output),
+        },
+        Some(output) => format!("{} -> {}", input, output),
+        None => format!("{}", input),
+    }
+}
+
+fn get_address_name(env: &Env, addr: &Address) -> Option<String> {
+    env.get_contract_name(addr)
+        .cloned()
+        .or_else(|| env.get_label(addr))
+}
+
+/// Collects the callers of delegate calls to unnamed contracts, which may be proxies
+fn collect_delegating_callers(env: &Env, frame: &CallFrame, callers: &mut Vec<Address>) {
+    for call in &frame.calls {
+        let is_unnamed = call
+            .to
+            .is_some_and(|addr| get_address_name(env, &addr).is_none());
+        if call.typ == "DELEGATECALL" && is_unnamed {
+            if let Some(caller) = frame.to.filter(|caller| !callers.contains(caller)) {
+                callers.push(caller);
+            }
+        }
+        collect_delegating_callers(env, call, callers);
+    }
+}
+
+/// Returns the implementation of each proxy delegating calls in the trace,
+/// so that only delegate calls to the actual implementation are labelled as such
+async fn get_implementations(env: &Env, frame: &CallFrame) -> HashMap<Address, Address> {
+    let mut callers = vec![];
+    collect_delegating_callers(env, frame, &mut callers);
+    let mut implementations = HashMap::new();
+    for caller in callers {
+        // detecting proxies is best-effort and only affects labels
+        if let Ok(Some(proxy)) = get_proxy(env, caller).await {
+            implementations.insert(caller, proxy.implementation);
+        }
     }
+    implementations
 }
 
-fn get_formatted_call(env: &Env, frame: &CallFrame) -> String {
+fn get_formatted_call(
+    env: &Env,
+    frame: &CallFrame,
+    parent: Option<&CallFrame>,
+    implementations: &HashMap<Address, Address>,
+) -> String {
     let mut formatted = "".to_string();
+    let proxy = parent
+        .filter(|_| frame.typ == "DELEGATECALL")
+        .and_then(|p| p.to)
+        .filter(|proxy| implementations.get(proxy) == frame.to.as_ref());
     if let Some(addr) = frame.to {
-        if let Some(contract) = env.get_contract_name(&addr) {
-            formatted.push_str(&format!("{}({})", &contract, addr));
-        } else {
-            formatted.push_str(&format!("{}", addr));
+        match (proxy, get_address_name(env, &addr)) {
+            (Some(proxy), None) => {
+                let proxy_name = get_address_name(env, &proxy).unwrap_or_else(|| proxy.to_string());
+                formatted.push_str(&format!("{} implementation({})", proxy_name, addr));
+            }
+            (_, Some(name)) => formatted.push_str(&format!("{}({})", name, addr)),
+            (None, None) => formatted.push_str(&format!("{}", addr)),
         }
     }
     formatted.push_str("::");
@@ -82,6 +135,8 @@ fn get_formatted_call(env: &Env, frame: &CallFrame) -> String {
 fn format_call(
     env: &Env,
     frame: &CallFrame,
+    parent: Option<&CallFrame>,
+    implementations: &HashMap<Address, Address>,
     depth: usize,
     wrap_opts: &textwrap::Options,
 ) -> String {
@@ -91,19 +146,27 @@ fn format_call(
         .clone()
         .initial_indent(&indent)
         .subsequent_indent(&subsequent_indent);
-    let call_str = get_formatted_call(env, frame);
+    let call_str = get_formatted_call(env, frame, parent, implementations);
     let rows = textwrap::wrap(&call_str, opts);
     let mut result = rows.iter().join("\n");
 
     for call in &frame.calls {
         result.push('\n');
-        result.push_str(&format_call(env, call, depth + 1, wrap_opts));
+        result.push_str(&format_call(
+            env,
+            call,
+            Some(frame),
+            implementations,
+            depth + 1,
+            wrap_opts,
+        ));
     }
 
     result
 }
 
-pub fn format_call_frame(env: &Env, frame: &CallFrame) -> String {
+pub async fn format_call_frame(env: &Env, frame: &CallFrame) -> String {
+    let implementations = get_implementations(env, frame).await;
     let wrap_opts = textwrap::Options::new(textwrap::termwidth() - 16).break_words(true);
-    format_call(env, frame, 0, &wrap_opts)
+    format_call(env, frame, None, &implementations, 0, &wrap_opts)
 }
@@ -0,0 +1,280 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use alloy::{
+    rpc::{
+        client::{BuiltInConnectionString, ClientBuilder, RpcClient},
+        json_rpc::{RequestPacket, ResponsePacket, ResponsePayload},
+    },
+    transports::{
+        http::Http, layers::RetryBackoffLayer, BoxTransport, TransportConnect, TransportError,
+        TransportFut,
+    },
+};
+use anyhow::Result;
+use tokio::sync::Mutex;
+use tower::{Layer, Service};
+use url::Url;
+
+use crate::loaders::four_bytes::FourBytesTransport;
+
+use super::{
+    cache::{CacheLayer, RpcCache},
+    recording::{RecordLayer, Recorder, ReplayTransport},
+    Config,
+};
+
+/// Spaces out requests to send at most a given number of requests per second
+#[derive(Debug)]
+struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_second: u32) -> Self {
+        RateLimiter {
+            interval: Duration::from_secs(1) / requests_per_second.max(1),
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn wait(&self) {
+        let mut next_slot = self.next_slot.lock().await;
+        let slot = (*next_slot).max(Instant::now());
+        *next_slot = slot + self.interval;
+        drop(next_slot);
+        tokio::time::sleep_until(slot.into()).await;
+    }
+}
+
+/// Layer limiting the rate of requests and logging them when enabled
+#[derive(Debug, Clone)]
+struct RpcLayer {
+    logging: Arc<AtomicBool>,
+    limiter: Option<Arc<RateLimiter>>,
+}
+
+impl<S> Layer<S> for RpcLayer {
+    type Service = RpcService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RpcService {
+            inner,
+            logging: self.logging.clone(),
+            limiter: self.limiter.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RpcService<S> {
+    inner: S,
+    logging: Arc<AtomicBool>,
+    limiter: Option<Arc<RateLimiter>>,
+}
+
+fn request_methods(request: &RequestPacket) -> String {
+    match request {
+        RequestPacket::Single(req) => req.method().to_string(),
+        RequestPacket::Batch(reqs) => {
+            let methods = reqs.iter().map(|r| r.method()).collect::<Vec<_>>();
+            format!("batch [{}]", methods.join(", "))
+        }
+    }
+}
+
+fn request_size(request: &RequestPacket) -> usize {
+    match request {
+        RequestPacket::Single(req) => req.serialized().get().len(),
+        RequestPacket::Batch(reqs) => reqs.iter().map(|r| r.serialized().get().len()).sum(),
+    }
+}
+
+fn response_size(response: &ResponsePacket) -> usize {
+    let payload_size = |payload: &ResponsePayload| match payload {
+        ResponsePayload::Success(data) => data.get().len(),
+        ResponsePayload::Failure(err) => err.message.len(),
+    };
+    match response {
+        ResponsePacket::Single(res) => payload_size(&res.payload),
+        ResponsePacket::Batch(res) => res.iter().map(|r| payload_size(&r.payload)).sum(),
+    }
+}
+
+impl<S> Service<RequestPacket> for RpcService<S>
+where
+    S: Service<RequestPacket, Response = ResponsePacket, Error = TransportError>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let mut inner = self.inner.clone();
+        let logging = self.logging.clone();
+        let limiter = self.limiter.clone();
+
+        Box::pin(async move {
+            if let Some(limiter) = limiter {
+                limiter.wait().await;
+            }
+            if !logging.load(Ordering::Relaxed) {
+                return inner.call(request).await;
+            }
+
+            let methods = request_methods(&request);
+            let sent = request_size(&request);
+            let start = Instant::now();
+            let result = inner.call(request).await;
+            let elapsed = start.elapsed().as_millis();
+            match &result {
+                Ok(response) => eprintln!(
+                    "[rpc] {} {}ms, sent {} bytes, received {} bytes",
+                    methods,
+                    elapsed,
+                    sent,
+                    response_size(response)
+                ),
+                Err(err) => eprintln!("[rpc] {} {}ms, failed: {}", methods, elapsed, err),
+            }
+            result
+        })
+    }
+}
+
+/// State shared by all the RPC clients of an environment
+#[derive(Debug, Clone)]
+pub struct TransportContext {
+    pub logging: Arc<AtomicBool>,
+    pub cache: Arc<RpcCache>,
+    recorder: Option<Arc<Recorder>>,
+    replay: Option<ReplayTransport>,
+}
+
+impl TransportContext {
+    pub fn new(config: &Config) -> Self {
+        TransportContext {
+            logging: Arc::new(AtomicBool::new(config.log_rpc || config.debug)),
+            cache: Arc::new(RpcCache::default()),
+            recorder: None,
+            replay: None,
+        }
+    }
+
+    /// Creates the context, setting up the recording if requested in the configuration
+    pub fn recording(config: &Config) -> Self {
+        let mut context = TransportContext::new(config);
+        context.recorder = config
+            .record
+            .as_ref()
+            .map(|path| Arc::new(Recorder::new(path.clone())));
+        context
+    }
+
+    /// Creates the context, setting up the recording or loading the recorded calls to replay
+    /// if requested in the configuration
+    pub fn load(config: &Config) -> Result<Self> {
+        let mut context = TransportContext::recording(config);
+        context.replay = config
+            .replay
+            .as_ref()
+            .map(|path| ReplayTransport::load(path))
+            .transpose()?;
+        Ok(context)
+    }
+}
+
+/// Wraps the transport with the recording and cache layers when enabled, then a retry layer,
+/// backing off exponentially on rate limits and transient errors,
+/// followed by the rate limiting and logging layer.
+/// Local endpoints are never cached as forks can diverge from the chain state
+fn build_client(
+    transport: BoxTransport,
+    is_local: bool,
+    config: &Config,
+    context: &TransportContext,
+) -> RpcClient {
+    let cache = (config.rpc_cache && !is_local).then(|| context.cache.clone());
+    let retry_layer = RetryBackoffLayer::new(
+        config.max_retries,
+        config.initial_backoff,
+        config.compute_units_per_second,
+    );
+    let rpc_layer = RpcLayer {
+        logging: context.logging.clone(),
+        limiter: config
+            .requests_per_second
+            .map(|rps| Arc::new(RateLimiter::new(rps))),
+    };
+    ClientBuilder::default()
+        .layer(RecordLayer::new(context.recorder.clone()))
+        .layer(CacheLayer::new(cache))
+        .layer(retry_layer)
+        .layer(rpc_layer)
+        .transport(transport, is_local)
+}
+
+/// Serves all the requests from the recorded calls, without any network access
+fn replay_client(replay: &ReplayTransport, context: &TransportContext) -> RpcClient {
+    let rpc_layer = RpcLayer {
+        logging: context.logging.clone(),
+        limiter: None,
+    };
+    ClientBuilder::default()
+        .layer(rpc_layer)
+        .transport(replay.clone(), true)
+}
+
+/// Client looking up function signatures on 4byte.directory,
+/// recorded and replayed along with the RPC calls
+pub fn signatures_client(context: &TransportContext) -> RpcClient {
+    if let Some(replay) = &context.replay {
+        return replay_client(replay, context);
+    }
+    ClientBuilder::default()
+        .layer(RecordLayer::new(context.recorder.clone()))
+        .transport(FourBytesTransport, false)
+}
+
+pub fn http_client(url: Url, config: &Config, context: &TransportContext) -> RpcClient {
+    if let Some(replay) = &context.replay {
+        return replay_client(replay, context);
+    }
+    let transport = Http::new(url);
+    let is_local = transport.guess_local();
+    build_client(BoxTransport::new(transport), is_local, config, context)
+}
+
+/// Connects to an HTTP, WebSocket or IPC endpoint depending on the URL
+pub async fn connect_client(
+    url: &str,
+    config: &Config,
+    context: &TransportContext,
+) -> Result<RpcClient> {
+    if let Some(replay) = &context.replay {
+        return Ok(replay_client(replay, context));
+    }
+    let connection: BuiltInConnectionString = url.parse()?;
+    let transport = connection.connect_boxed().await?;
+    Ok(build_client(
+        transport,
+        connection.is_local(),
+        config,
+        context,
+    ))
+}
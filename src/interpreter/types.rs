@@ -88,15 +88,12 @@ impl TryFrom<Value> for ArrayIndex {
 pub struct ContractInfo(pub String, pub JsonAbi);
 
 impl ContractInfo {
-    pub fn make_function(&self, name: &str, addr: Address) -> Result<Function> {
+    pub fn make_function(&self, name: &str, receiver: &Value) -> Result<Function> {
         let _func = self
             .1
             .function(name)
             .ok_or_else(|| anyhow!("function {} not found in contract {}", name, self.0))?;
-        Ok(Function::new(
-            ContractFunction::arc(name),
-            Some(&Value::Contract(self.clone(), addr)),
-        ))
+        Ok(Function::new(ContractFunction::arc(name), Some(receiver)))
     }
 
     pub fn member_access(&self, name: &str) -> Result<Value> {
@@ -127,6 +124,7 @@ pub enum NonParametricType {
     Bool,
     Int,
     Uint,
+    Decimal,
     FixBytes,
     Bytes,
     String,
@@ -141,6 +139,7 @@ pub enum NonParametricType {
     Transaction,
     Function,
     Repl,
+    Cache,
     Accounts,
     Vm,
     Block,
@@ -149,6 +148,11 @@ pub enum NonParametricType {
     Json,
     Events,
     Abi,
+    Ens,
+    Labels,
+    Storage,
+    Sig,
+    Permit,
     Type,
 }
 
@@ -160,6 +164,7 @@ pub enum Type {
     Bool,
     Int(usize),
     Uint(usize),
+    Decimal,
     FixBytes(usize),
     Bytes,
     String,
@@ -175,6 +180,7 @@ pub enum Type {
     Function,
     Accounts,
     Repl,
+    Cache,
     Vm,
     Block,
     Console,
@@ -182,6 +188,11 @@ pub enum Type {
     Json,
     Events,
     Abi,
+    Ens,
+    Labels,
+    Storage,
+    Sig,
+    Permit,
     Type(Box<Type>),
 }
 
@@ -194,6 +205,7 @@ impl Display for Type {
             Type::Bool => write!(f, "bool"),
             Type::Int(size) => write!(f, "int{}", size),
             Type::Uint(size) => write!(f, "uint{}", size),
+            Type::Decimal => write!(f, "decimal"),
             Type::FixBytes(size) => write!(f, "bytes{}", size),
             Type::Bytes => write!(f, "bytes"),
             Type::String => write!(f, "string"),
@@ -217,6 +229,7 @@ impl Display for Type {
 
             Type::Accounts => write!(f, "accounts"),
             Type::Repl => write!(f, "repl"),
+            Type::Cache => write!(f, "cache"),
             Type::Vm => write!(f, "vm"),
             Type::Block => write!(f, "block"),
             Type::Events => write!(f, "events"),
@@ -224,6 +237,11 @@ impl Display for Type {
             Type::Json => write!(f, "json"),
             Type::Fs => write!(f, "fs"),
             Type::Abi => write!(f, "abi"),
+            Type::Ens => write!(f, "ens"),
+            Type::Labels => write!(f, "labels"),
+            Type::Storage => write!(f, "storage"),
+            Type::Sig => write!(f, "sig"),
+            Type::Permit => write!(f, "permit"),
             Type::Type(t) => write!(f, "type({})", t),
         }
     }
@@ -238,6 +256,7 @@ impl<T: AsRef<Type>> From<T> for NonParametricType {
             Type::Bool => NonParametricType::Bool,
             Type::Int(_) => NonParametricType::Int,
             Type::Uint(_) => NonParametricType::Uint,
+            Type::Decimal => NonParametricType::Decimal,
             Type::FixBytes(_) => NonParametricType::FixBytes,
             Type::Bytes => NonParametricType::Bytes,
             Type::String => NonParametricType::String,
@@ -253,6 +272,7 @@ impl<T: AsRef<Type>> From<T> for NonParametricType {
             Type::Accounts => NonParametricType::Accounts,
             Type::Transaction => NonParametricType::Transaction,
             Type::Repl => NonParametricType::Repl,
+            Type::Cache => NonParametricType::Cache,
             Type::Vm => NonParametricType::Vm,
             Type::Block => NonParametricType::Block,
             Type::Console => NonParametricType::Console,
@@ -260,6 +280,11 @@ impl<T: AsRef<Type>> From<T> for NonParametricType {
             Type::Json => NonParametricType::Json,
             Type::Events => NonParametricType::Events,
             Type::Abi => NonParametricType::Abi,
+            Type::Ens => NonParametricType::Ens,
+            Type::Labels => NonParametricType::Labels,
+            Type::Storage => NonParametricType::Storage,
+            Type::Sig => NonParametricType::Sig,
+            Type::Permit => NonParametricType::Permit,
             Type::Type(_) => NonParametricType::Type,
         }
     }
@@ -470,8 +495,10 @@ impl Type {
         match (self, value) {
             (Type::Any, value) => Ok(value.clone()),
             (type_, value) if type_ == &value.get_type() => Ok(value.clone()),
-            (Type::Contract(info), Value::Addr(addr)) => Ok(Value::Contract(info.clone(), *addr)),
-            (Type::Address, Value::Contract(_, addr)) => Ok(Value::Addr(*addr)),
+            (Type::Contract(info), Value::Addr(addr)) => {
+                Ok(Value::Contract(info.clone(), *addr, None))
+            }
+            (Type::Address, Value::Contract(_, addr, _)) => Ok(Value::Addr(*addr)),
             (Type::Address, Value::Uint(v, _)) => Ok(Value::Addr(Address::from(v.to::<U160>()))),
             (Type::Address, Value::Int(v, _)) if v.is_zero() => Ok(Value::Addr(Address::ZERO)),
             (Type::Address, Value::Int(_, _)) => {
@@ -494,6 +521,7 @@ impl Type {
             }
             (Type::Uint(size), Value::Uint(v, _)) => Value::Uint(*v, *size).validate_int(),
             (Type::Int(size), Value::Int(v, _)) => Value::Int(*v, *size).validate_int(),
+            (Type::Decimal, Value::Uint(v, _)) => Ok(Value::Decimal(*v, 0)),
             (Type::Int(size), Value::Uint(v, _)) => {
                 Value::Int((*v).try_into()?, *size).validate_int()
             }
@@ -517,8 +545,8 @@ impl Type {
                 let bytes = to_fixed_bytes(&addr.0 .0, 20, false)?;
                 Ok(Value::FixBytes(bytes, 20))
             }
-            (Type::Transaction, Value::FixBytes(v, 32)) => Ok(Value::Transaction(*v)),
-            (Type::FixBytes(32), Value::Transaction(t)) => Ok(Value::FixBytes(*t, 32)),
+            (Type::Transaction, Value::FixBytes(v, 32)) => Ok(Value::Transaction(*v, None)),
+            (Type::FixBytes(32), Value::Transaction(t, _)) => Ok(Value::FixBytes(*t, 32)),
             (Type::Bytes, Value::Str(v)) => Ok(Value::Bytes(v.as_bytes().to_vec())),
             (type_ @ Type::FixBytes(_), Value::Str(_)) => type_.cast(&Type::Bytes.cast(value)?),
             (Type::Bytes, Value::FixBytes(v, s)) => Ok(Value::Bytes(v.0[..*s].to_vec())),
@@ -5,13 +5,19 @@ use std::str::FromStr;
 
 use alloy::{
     dyn_abi::{EventExt, JsonAbiExt},
-    json_abi::Event,
+    json_abi::{Error, Event},
     primitives::{FixedBytes, B256, U256},
     rpc::types::{Log, TransactionReceipt},
+    sol_types::PanicKind,
 };
 
+use crate::loaders;
+
 use super::{types::HashableIndexMap, Env, Type, Value};
 
+const REVERT_ERROR_SIGNATURE: &str = "Error(string)";
+const PANIC_ERROR_SIGNATURE: &str = "Panic(uint256)";
+
 pub fn join_with_final<T>(separator: &str, final_separator: &str, strings: Vec<T>) -> String
 where
     T: std::string::ToString,
@@ -59,6 +65,22 @@ pub fn parse_rational_literal(whole: &str, raw_fraction: &str, raw_exponent: &st
     Ok(n)
 }
 
+/// Parses a rational literal, returning a decimal value when it is not an integer
+pub fn parse_decimal_literal(whole: &str, raw_fraction: &str, raw_exponent: &str) -> Result<Value> {
+    let fraction = raw_fraction.trim_end_matches('0');
+    let exponent = if raw_exponent.is_empty() {
+        0
+    } else {
+        raw_exponent.parse::<usize>()?
+    };
+    if fraction.len() <= exponent {
+        return parse_rational_literal(whole, raw_fraction, raw_exponent)
+            .map(|v| Value::Uint(v, 256));
+    }
+    let digits = U256::from_str(&format!("{}{}", whole, fraction))?;
+    Ok(Value::Decimal(digits, fraction.len() - exponent))
+}
+
 pub fn decode_log_args(log: &Log, event: &Event) -> Result<Value> {
     let decoded = event.decode_log(log.data())?;
     let mut fully_decoded = IndexMap::new();
@@ -90,18 +112,81 @@ pub fn decode_error(env: &Env, data: &[u8]) -> Result<Value> {
     let error = env
         .get_error(&selector)
         .ok_or(anyhow!("error with selector {} not found", selector))?;
+    decode_error_with(error, data)
+}
+
+fn decode_error_with(error: &Error, data: &[u8]) -> Result<Value> {
     let decoded = error.abi_decode_input(&data[4..])?;
-    let values = decoded
+    let mut values = decoded
         .into_iter()
         .map(Value::try_from)
         .collect::<Result<Vec<_>>>()?;
+    if error.signature() == PANIC_ERROR_SIGNATURE {
+        if let [Value::Uint(code, _)] = values.as_slice() {
+            values = vec![Value::Str(panic_reason(*code))];
+        }
+    }
     Ok(Value::Tuple(vec![
         Value::Str(error.signature()),
         Value::Tuple(values),
     ]))
 }
 
+/// Decodes revert data returned by a failed call or transaction.
+/// Known errors, including the builtin `Error(string)` and `Panic(uint256)`,
+/// are tried first, then the 4byte directory.
+pub async fn decode_revert(env: &mut Env, data: &[u8]) -> Result<Value> {
+    if data.len() < 4 {
+        bail!("revert data is too short");
+    }
+    if let Ok(value) = decode_error(env, data) {
+        return Ok(value);
+    }
+    let selector = FixedBytes::<4>::from_slice(&data[..4]);
+    let client = env.get_signatures_client();
+    let func = loaders::four_bytes::find_function(&client, selector).await?;
+    let error = Error {
+        name: func.name,
+        inputs: func.inputs,
+    };
+    env.register_error(error.clone());
+    decode_error_with(&error, data)
+}
+
+pub fn builtin_errors() -> Vec<Error> {
+    [REVERT_ERROR_SIGNATURE, PANIC_ERROR_SIGNATURE]
+        .iter()
+        .map(|s| Error::parse(s).expect("valid builtin error signature"))
+        .collect()
+}
+
+pub fn panic_reason(code: U256) -> String {
+    let kind = u32::try_from(code).ok().and_then(PanicKind::from_number);
+    match kind {
+        Some(kind) => format!("{} (0x{:x})", kind, code),
+        None => format!("unknown panic code (0x{:x})", code),
+    }
+}
+
+/// Same as [`decode_revert`] but falls back to the raw data when it cannot be decoded
+pub async fn format_revert(env: &mut Env, data: &[u8]) -> String {
+    if data.is_empty() {
+        return "execution reverted".to_string();
+    }
+    match decode_revert(env, data).await {
+        Ok(value) => value.to_string(),
+        Err(_) => format!("0x{}", alloy::hex::encode(data)),
+    }
+}
+
 pub fn log_to_value(env: &Env, log: Log) -> Result<Value> {
+    let event = log.topic0().and_then(|t| env.get_event(t));
+    log_to_value_with_event(log, event)
+}
+
+/// Converts a log to a value, decoding its arguments with the given event
+/// rather than the one registered for its selector
+pub fn log_to_value_with_event(log: Log, event: Option<&Event>) -> Result<Value> {
     let mut fields = IndexMap::new();
     fields.insert("address".to_string(), Value::Addr(log.address()));
     fields.insert(
@@ -137,7 +222,7 @@ pub fn log_to_value(env: &Env, log: Log) -> Result<Value> {
         );
     }
 
-    if let Some(evt) = log.topic0().and_then(|t| env.get_event(t)) {
+    if let Some(evt) = event {
         let decoded_args = decode_log_args(&log, evt)?;
         fields.insert("args".to_string(), decoded_args);
     } else {
@@ -150,6 +235,15 @@ pub fn log_to_value(env: &Env, log: Log) -> Result<Value> {
     ))
 }
 
+/// Runs the future until it completes or Ctrl-C is pressed,
+/// in which case it is cancelled and `None` is returned
+pub async fn run_until_interrupted<F: std::future::Future>(future: F) -> Option<F::Output> {
+    tokio::select! {
+        result = future => Some(result),
+        _ = tokio::signal::ctrl_c() => None,
+    }
+}
+
 pub fn receipt_to_value(env: &Env, receipt: TransactionReceipt) -> Result<Value> {
     let logs = receipt.inner.logs().to_vec();
     let transformed_logs = logs
@@ -242,6 +336,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_decimal_literal() {
+        assert_eq!(
+            parse_decimal_literal("1", "2", "3").unwrap(),
+            Value::Uint(U256::from(1200), 256)
+        );
+        let decimal = parse_decimal_literal("1", "50", "").unwrap();
+        assert_eq!(decimal, Value::Decimal(U256::from(15), 1));
+        assert_eq!(decimal.to_string(), "1.5");
+        let decimal = parse_decimal_literal("", "0125", "1").unwrap();
+        assert_eq!(decimal, Value::Decimal(U256::from(125), 3));
+        assert_eq!(decimal.to_string(), "0.125");
+    }
+
     #[test]
     fn test_to_fixed_bytes() {
         assert_eq!(
@@ -324,6 +432,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_decode_builtin_errors() {
+        let errors = builtin_errors();
+        let revert = &errors[0];
+        let data = bytes!("08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000036261640000000000000000000000000000000000000000000000000000000000");
+        assert_eq!(
+            decode_error_with(revert, &data).unwrap(),
+            Value::Tuple(vec![
+                Value::Str("Error(string)".to_string()),
+                Value::Tuple(vec![Value::Str("bad".to_string())])
+            ])
+        );
+
+        let panic = &errors[1];
+        let data =
+            bytes!("4e487b710000000000000000000000000000000000000000000000000000000000000011");
+        assert_eq!(
+            decode_error_with(panic, &data).unwrap(),
+            Value::Tuple(vec![
+                Value::Str("Panic(uint256)".to_string()),
+                Value::Tuple(vec![Value::Str(
+                    "arithmetic underflow or overflow (0x11)".to_string()
+                )])
+            ])
+        );
+    }
+
+    #[test]
+    fn test_panic_reason() {
+        assert_eq!(
+            panic_reason(U256::from(0x12)),
+            "division or modulo by zero (0x12)"
+        );
+        assert_eq!(
+            panic_reason(U256::from(0x32)),
+            "array out-of-bounds access (0x32)"
+        );
+        assert_eq!(panic_reason(U256::from(0x99)), "unknown panic code (0x99)");
+    }
+
     fn _get_log() -> Log {
         Log {
             inner: alloy::primitives::Log {
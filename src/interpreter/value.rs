@@ -1,6 +1,9 @@
 use alloy::{
     dyn_abi::DynSolValue,
-    eips::{BlockId, BlockNumberOrTag},
+    eips::{
+        eip7702::{Authorization, SignedAuthorization},
+        BlockId, BlockNumberOrTag,
+    },
     hex::{self, FromHex},
     primitives::{Address, B256, I256, U256},
     rpc::types::TransactionReceipt,
@@ -27,17 +30,19 @@ pub enum Value {
     Bool(bool),
     Int(I256, usize),
     Uint(U256, usize),
+    /// Non-integer number literal, stored as its digits and number of decimals
+    Decimal(U256, usize),
     Str(String),
     FixBytes(B256, usize),
     Bytes(Vec<u8>),
     Addr(Address),
-    Contract(ContractInfo, Address),
+    Contract(ContractInfo, Address, Option<String>),
     Tuple(Vec<Value>),
     NamedTuple(String, HashableIndexMap<String, Value>),
     Array(Vec<Value>, Box<Type>),
     Mapping(HashableIndexMap<Value, Value>, Box<Type>, Box<Type>),
     TypeObject(Type),
-    Transaction(B256),
+    Transaction(B256, Option<String>),
     Func(Box<Function>),
 }
 
@@ -69,6 +74,12 @@ impl Display for Value {
             Value::Bool(b) => write!(f, "{}", b),
             Value::Int(n, _) => write!(f, "{}", n),
             Value::Uint(n, _) => write!(f, "{}", n),
+            Value::Decimal(n, 0) => write!(f, "{}", n),
+            Value::Decimal(n, decimals) => {
+                let digits = format!("{:0>width$}", n.to_string(), width = decimals + 1);
+                let (whole, fraction) = digits.split_at(digits.len() - decimals);
+                write!(f, "{}.{}", whole, fraction)
+            }
             Value::Addr(a) => write!(f, "{}", a.to_checksum(None)),
             Value::Str(s) => write!(f, "\"{}\"", s),
             Value::FixBytes(w, s) => {
@@ -86,10 +97,16 @@ impl Display for Value {
                 write!(f, "mapping({} => {}) {{ {} }}", kt, vt, values)
             }
             Value::TypeObject(t) => write!(f, "{}", t),
-            Value::Transaction(t) => write!(f, "Transaction({})", t),
-            Value::Contract(ContractInfo(name, _), addr) => {
+            Value::Transaction(t, None) => write!(f, "Transaction({})", t),
+            Value::Transaction(t, Some(chain)) => {
+                write!(f, "Transaction({}).on(\"{}\")", t, chain)
+            }
+            Value::Contract(ContractInfo(name, _), addr, None) => {
                 write!(f, "{}({})", name, addr.to_checksum(None))
             }
+            Value::Contract(ContractInfo(name, _), addr, Some(chain)) => {
+                write!(f, "{}({}).on(\"{}\")", name, addr.to_checksum(None), chain)
+            }
             Value::Func(func) => write!(f, "{}", func),
         }
     }
@@ -106,9 +123,9 @@ impl TryFrom<&Value> for alloy::dyn_abi::DynSolValue {
             Value::Str(s) => DynSolValue::String(s.clone()),
             Value::Addr(a) => DynSolValue::Address(*a),
             Value::FixBytes(w, s) => DynSolValue::FixedBytes(*w, *s),
-            Value::Transaction(t) => DynSolValue::FixedBytes(*t, 32),
+            Value::Transaction(t, _) => DynSolValue::FixedBytes(*t, 32),
             Value::Bytes(b) => DynSolValue::Bytes(b.clone()),
-            Value::Contract(_, addr) => DynSolValue::Address(*addr),
+            Value::Contract(_, addr, _) => DynSolValue::Address(*addr),
             Value::NamedTuple(name, vs) => {
                 let prop_names = vs.0.iter().map(|(k, _)| k.clone()).collect();
                 let tuple =
@@ -124,6 +141,7 @@ impl TryFrom<&Value> for alloy::dyn_abi::DynSolValue {
             }
             Value::Tuple(vs) => DynSolValue::Tuple(_values_to_dyn_sol_values(vs)?),
             Value::Array(vs, _) => DynSolValue::Array(_values_to_dyn_sol_values(vs)?),
+            Value::Decimal(..) => bail!("cannot convert decimal to Solidity type"),
             Value::Mapping(_, _, _) => bail!("cannot convert mapping to Solidity type"),
             Value::Null => bail!("cannot convert null to Solidity type"),
             Value::TypeObject(_) => bail!("cannot convert type objects to Solidity type"),
@@ -157,6 +175,9 @@ impl Serialize for Value {
                     serializer.serialize_str(&n.to_string())
                 }
             }
+            Value::Decimal(..) => {
+                serializer.serialize_f64(self.as_f64().map_err(serde::ser::Error::custom)?)
+            }
             Value::Str(s) => serializer.serialize_str(s),
             Value::FixBytes(w, s) => {
                 let bytes = w[0..*s].to_vec();
@@ -164,7 +185,7 @@ impl Serialize for Value {
             }
             Value::Bytes(bytes) => serializer.serialize_str(&format!("0x{}", hex::encode(bytes))),
             Value::Addr(a) => serializer.serialize_str(&a.to_checksum(None)),
-            Value::Contract(ContractInfo(_name, _), addr) => {
+            Value::Contract(ContractInfo(_name, _), addr, _) => {
                 serializer.serialize_str(&addr.to_checksum(None))
             }
             Value::Tuple(v) => v.serialize(serializer),
@@ -178,7 +199,7 @@ impl Serialize for Value {
             Value::Array(v, _) => v.serialize(serializer),
             Value::Mapping(v, _, _) => v.0.serialize(serializer),
             Value::TypeObject(t) => serializer.serialize_str(&format!("{}", t)),
-            Value::Transaction(t) => serializer.serialize_str(&format!("0x{}", hex::encode(t))),
+            Value::Transaction(t, _) => serializer.serialize_str(&format!("0x{}", hex::encode(t))),
             Value::Func(func) => serializer.serialize_str(&format!("{}", func)),
         }
     }
@@ -204,6 +225,33 @@ impl From<alloy::rpc::types::Log> for Value {
     }
 }
 
+impl From<SignedAuthorization> for Value {
+    fn from(authorization: SignedAuthorization) -> Self {
+        let inner = authorization.inner();
+        let mut fields = IndexMap::new();
+        fields.insert("chainId".to_string(), Value::Uint(inner.chain_id, 256));
+        fields.insert("address".to_string(), Value::Addr(inner.address));
+        fields.insert(
+            "nonce".to_string(),
+            Value::Uint(U256::from(inner.nonce), 64),
+        );
+        fields.insert(
+            "yParity".to_string(),
+            Value::Uint(U256::from(authorization.y_parity()), 8),
+        );
+        fields.insert(
+            "r".to_string(),
+            Value::FixBytes(authorization.r().into(), 32),
+        );
+        fields.insert(
+            "s".to_string(),
+            Value::FixBytes(authorization.s().into(), 32),
+        );
+
+        Value::NamedTuple("Authorization".to_string(), HashableIndexMap(fields))
+    }
+}
+
 impl TryFrom<alloy::dyn_abi::DynSolValue> for Value {
     type Error = anyhow::Error;
 
@@ -386,18 +434,58 @@ impl PartialOrd for Value {
             (Value::FixBytes(a, _), Value::FixBytes(b, _)) => a.partial_cmp(b),
             (Value::Tuple(a), Value::Tuple(b)) => a.partial_cmp(b),
             (Value::Array(a, _), Value::Array(b, _)) => a.partial_cmp(b),
-            (Value::Contract(_, a), Value::Contract(_, b)) => a.partial_cmp(b),
+            (Value::Contract(_, a, _), Value::Contract(_, b, _)) => a.partial_cmp(b),
             _ => None,
         }
     }
 }
 
 impl Value {
+    /// Formats the value like `Display`, appending the label of the addresses it contains
+    pub fn format_with_labels(&self, get_label: &dyn Fn(&Address) -> Option<String>) -> String {
+        let format_values = |values: &[Value]| {
+            values
+                .iter()
+                .map(|v| v.format_with_labels(get_label))
+                .join(", ")
+        };
+        match self {
+            Value::Addr(a) => match get_label(a) {
+                Some(label) => format!("{} ({})", self, label),
+                None => self.to_string(),
+            },
+            Value::NamedTuple(name, v) => {
+                let fields =
+                    v.0.iter()
+                        .map(|(k, v)| format!("{}: {}", k, v.format_with_labels(get_label)))
+                        .join(", ");
+                format!("{} {{ {} }}", name, fields)
+            }
+            Value::Tuple(v) => format!("({})", format_values(v)),
+            Value::Array(v, _) => format!("[{}]", format_values(v)),
+            Value::Mapping(v, kt, vt) => {
+                let values =
+                    v.0.iter()
+                        .map(|(k, v)| {
+                            format!(
+                                "{}: {}",
+                                k.format_with_labels(get_label),
+                                v.format_with_labels(get_label)
+                            )
+                        })
+                        .join(", ");
+                format!("mapping({} => {}) {{ {} }}", kt, vt, values)
+            }
+            _ => self.to_string(),
+        }
+    }
+
     pub fn get_type(&self) -> Type {
         match self {
             Value::Bool(_) => Type::Bool,
             Value::Int(_, s) => Type::Int(*s),
             Value::Uint(_, s) => Type::Uint(*s),
+            Value::Decimal(..) => Type::Decimal,
             Value::Str(_) => Type::String,
             Value::Addr(_) => Type::Address,
             Value::FixBytes(_, s) => Type::FixBytes(*s),
@@ -411,12 +499,12 @@ impl Value {
             Value::Tuple(vs) => Type::Tuple(vs.iter().map(Value::get_type).collect()),
             Value::Array(_, t) => Type::Array(t.clone()),
             Value::Mapping(_, kt, vt) => Type::Mapping(kt.clone(), vt.clone()),
-            Value::Contract(c, _) => Type::Contract(c.clone()),
+            Value::Contract(c, _, _) => Type::Contract(c.clone()),
             Value::Null => Type::Null,
             Value::Func(_) => Type::Function,
             Value::TypeObject(type_ @ Type::Type(_)) => type_.clone(),
             Value::TypeObject(type_) => Type::Type(Box::new(type_.clone())),
-            Value::Transaction(_) => Type::Transaction,
+            Value::Transaction(_, _) => Type::Transaction,
         }
     }
 
@@ -495,9 +583,9 @@ impl Value {
         }
     }
 
-    pub fn as_contract(&self) -> Result<(ContractInfo, Address)> {
+    pub fn as_contract(&self) -> Result<(ContractInfo, Address, Option<String>)> {
         match self {
-            Value::Contract(info, addr) => Ok((info.clone(), *addr)),
+            Value::Contract(info, addr, chain) => Ok((info.clone(), *addr, chain.clone())),
             _ => bail!("cannot convert {} to contract", self.get_type()),
         }
     }
@@ -509,6 +597,13 @@ impl Value {
         }
     }
 
+    pub fn as_bool(&self) -> Result<bool> {
+        match self {
+            Value::Bool(b) => Ok(*b),
+            _ => bail!("cannot convert {} to bool", self.get_type()),
+        }
+    }
+
     pub fn as_type(&self) -> Result<Type> {
         match self {
             Value::TypeObject(type_) => Ok(type_.clone()),
@@ -560,6 +655,22 @@ impl Value {
         }
     }
 
+    /// Returns the digits and the number of decimals of a decimal or unsigned value
+    pub fn as_decimal(&self) -> Result<(U256, usize)> {
+        match self {
+            Value::Uint(n, _) => Ok((*n, 0)),
+            Value::Decimal(n, decimals) => Ok((*n, *decimals)),
+            _ => bail!("cannot convert {} to decimal", self.get_type()),
+        }
+    }
+
+    pub fn as_f64(&self) -> Result<f64> {
+        match self {
+            Value::Uint(..) | Value::Decimal(..) => Ok(self.to_string().parse()?),
+            _ => bail!("cannot convert {} to f64", self.get_type()),
+        }
+    }
+
     pub fn as_b256(&self) -> Result<B256> {
         match Type::FixBytes(32).cast(self) {
             Ok(Value::FixBytes(n, 32)) => Ok(n),
@@ -583,6 +694,24 @@ impl Value {
         }
     }
 
+    pub fn as_authorization(&self) -> Result<SignedAuthorization> {
+        let authorization = Authorization {
+            chain_id: self.get_field("chainId")?.as_u256()?,
+            address: self.get_field("address")?.as_address()?,
+            nonce: self.get_field("nonce")?.as_u64()?,
+        };
+        let y_parity = u8::try_from(self.get_field("yParity")?.as_u64()?)
+            .map_err(|_| anyhow!("invalid authorization yParity"))?;
+        let r = U256::from_be_bytes(self.get_field("r")?.as_b256()?.0);
+        let s = U256::from_be_bytes(self.get_field("s")?.as_b256()?.0);
+        Ok(SignedAuthorization::new_unchecked(
+            authorization,
+            y_parity,
+            r,
+            s,
+        ))
+    }
+
     pub fn as_vec(&self) -> Result<&Vec<Value>> {
         match self {
             Value::Array(arr, _) => Ok(arr),
@@ -629,7 +758,9 @@ impl Value {
             Value::NamedTuple(_, kv) if kv.0.contains_key(member) => {
                 Ok(kv.0.get(member).unwrap().clone())
             }
-            Value::Contract(c, addr) => c.make_function(member, *addr).map(Into::into),
+            Value::Contract(c, _, _) if c.1.function(member).is_some() => {
+                c.make_function(member, self).map(Into::into)
+            }
             Value::Func(f) => f.member_access(member),
             Value::TypeObject(Type::Contract(c)) => c.member_access(member),
             _ => {
@@ -828,6 +959,28 @@ impl Shr for Value {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_format_with_labels() {
+        let labelled = Address::repeat_byte(1);
+        let addresses = Value::Array(
+            vec![Value::Addr(labelled), Value::Addr(Address::ZERO)],
+            Box::new(Type::Address),
+        );
+        let get_label = |addr: &Address| (*addr == labelled).then(|| "Treasury".to_string());
+        assert_eq!(
+            addresses.format_with_labels(&get_label),
+            format!(
+                "[{} (Treasury), {}]",
+                labelled.to_checksum(None),
+                Address::ZERO.to_checksum(None)
+            )
+        );
+        assert_eq!(
+            Value::Addr(labelled).to_string(),
+            labelled.to_checksum(None)
+        );
+    }
+
     #[test]
     fn test_add() {
         assert_eq!(
@@ -1,10 +1,25 @@
-use alloy::{json_abi::Function, primitives::FixedBytes, transports::http::reqwest};
+use std::task::{Context, Poll};
+
+use alloy::{
+    json_abi::Function,
+    primitives::FixedBytes,
+    rpc::{
+        client::RpcClient,
+        json_rpc::{RequestPacket, Response, ResponsePacket, ResponsePayload, SerializedRequest},
+    },
+    transports::{http::reqwest, TransportError, TransportErrorKind, TransportFut},
+};
 use anyhow::{anyhow, bail, Result};
 use itertools::Itertools;
 use serde_json::Value;
+use tower::Service;
 
 const FOUR_BYTES_API_URL: &str = "https://www.4byte.directory/api/v1/signatures/";
 
+/// Method used to look up the signature of a selector through an RPC client,
+/// so that lookups can be recorded, replayed and mocked like any other request
+pub const SIGNATURE_METHOD: &str = "4byte_getSignature";
+
 async fn get_results(selector_str: &str) -> Result<Vec<Value>> {
     let url = format!("{}?hex_signature={}", FOUR_BYTES_API_URL, selector_str);
     let response = reqwest::get(url).await?;
@@ -15,7 +30,17 @@ async fn get_results(selector_str: &str) -> Result<Vec<Value>> {
         .ok_or(anyhow!("No results found for selector {}", selector_str))
 }
 
-pub async fn find_function(selector: FixedBytes<4>) -> Result<Function> {
+/// Returns the first registered signature among the results
+fn first_signature(results: &[Value]) -> Option<String> {
+    results
+        .iter()
+        .sorted_by_key(|r| r["id"].as_u64())
+        .next()
+        .and_then(|r| r["text_signature"].as_str())
+        .map(|s| s.to_string())
+}
+
+async fn get_signature(selector: FixedBytes<4>) -> Result<String> {
     // NOTE: 4byte.directory API seems to be senstitive to 0x prefix and is not consistent across functions
     let mut results = get_results(&selector.to_string()).await?;
     if results.is_empty() {
@@ -24,27 +49,93 @@ pub async fn find_function(selector: FixedBytes<4>) -> Result<Function> {
     if results.is_empty() {
         bail!("No results found for selector {}", selector);
     }
-    let desired_result = results
-        .iter()
-        .sorted_by_key(|r| r["id"].as_u64()) // get first registered signature
-        .next()
-        .unwrap();
-    let signature = desired_result["text_signature"]
-        .as_str()
-        .ok_or(anyhow!("No text signature found for selector {}", selector))?;
-    Function::parse(signature).map_err(|e| anyhow!(e))
+    first_signature(&results).ok_or(anyhow!("No text signature found for selector {}", selector))
+}
+
+async fn answer(request: SerializedRequest) -> Result<Response, TransportError> {
+    let (selector,): (FixedBytes<4>,) = request
+        .params()
+        .ok_or_else(|| TransportErrorKind::custom_str("missing selector"))
+        .and_then(|p| {
+            serde_json::from_str(p.get()).map_err(|e| TransportError::deser_err(e, p.get()))
+        })?;
+    let signature = get_signature(selector)
+        .await
+        .map_err(|e| TransportErrorKind::custom_str(&e.to_string()))?;
+    let result = serde_json::value::to_raw_value(&signature).map_err(TransportError::ser_err)?;
+    Ok(Response {
+        id: request.id().clone(),
+        payload: ResponsePayload::Success(result),
+    })
+}
+
+/// Transport answering signature lookups from the 4byte.directory API
+#[derive(Debug, Clone)]
+pub struct FourBytesTransport;
+
+impl Service<RequestPacket> for FourBytesTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        Box::pin(async move {
+            match request {
+                RequestPacket::Single(req) => answer(req).await.map(ResponsePacket::Single),
+                RequestPacket::Batch(reqs) => {
+                    let mut responses = vec![];
+                    for req in reqs {
+                        responses.push(answer(req).await?);
+                    }
+                    Ok(ResponsePacket::Batch(responses))
+                }
+            }
+        })
+    }
+}
+
+/// Looks up the function with the given selector, sending the lookup through `client`
+pub async fn find_function(client: &RpcClient, selector: FixedBytes<4>) -> Result<Function> {
+    let signature: String = client.request(SIGNATURE_METHOD, (selector,)).await?;
+    Function::parse(&signature).map_err(|e| anyhow!(e))
 }
 
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
+    use alloy::transports::mock::Asserter;
+    use serde_json::json;
+
     use super::*;
 
+    #[test]
+    fn test_first_signature() {
+        let results = json!([
+            {"id": 2, "text_signature": "executeL2Proposal(uint256)"},
+            {"id": 1, "text_signature": "executeL2Proposal()"},
+        ]);
+        assert_eq!(
+            first_signature(results.as_array().unwrap()),
+            Some("executeL2Proposal()".to_string())
+        );
+        assert_eq!(first_signature(&[]), None);
+    }
+
     #[tokio::test]
     async fn test_find_function() {
+        let asserter = Asserter::new();
+        let client = RpcClient::mocked(asserter.clone());
+        asserter.push_success(&"executeL2Proposal()");
         let selector = FixedBytes::from_str("0x1bcf634e").unwrap();
-        let function = find_function(selector).await.unwrap();
+        let function = find_function(&client, selector).await.unwrap();
         assert_eq!(function.name, "executeL2Proposal");
+
+        asserter.push_failure_msg("No results found for selector 0x1bcf634e");
+        assert!(find_function(&client, selector).await.is_err());
     }
 }
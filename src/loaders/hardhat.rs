@@ -1,7 +1,31 @@
 use anyhow::{anyhow, Result};
-use std::path::{Path, PathBuf};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
 
-use super::loader::ProjectLoader;
+use super::{loader::ProjectLoader, types::StorageLayout};
+
+/// Hardhat only includes the storage layouts in the compiler output of the build info files
+fn read_build_info_layouts(filepath: &Path) -> Result<HashMap<String, StorageLayout>> {
+    let reader = BufReader::new(File::open(filepath)?);
+    let json: Value = serde_json::from_reader(reader)?;
+    let mut layouts = HashMap::new();
+    let sources = json["output"]["contracts"]
+        .as_object()
+        .ok_or(anyhow!("invalid build info"))?;
+    for contracts in sources.values().filter_map(Value::as_object) {
+        for (contract_name, contract) in contracts {
+            if let Ok(layout) = StorageLayout::try_from(&contract["storageLayout"]) {
+                layouts.insert(contract_name.clone(), layout);
+            }
+        }
+    }
+    Ok(layouts)
+}
 
 pub struct HardhatProjectLoader;
 
@@ -28,6 +52,20 @@ impl ProjectLoader for HardhatProjectLoader {
             .map(|s| s.to_string())
     }
 
+    fn load_storage_layouts(&self, directory: &Path) -> HashMap<String, StorageLayout> {
+        let pattern = directory.join("artifacts/build-info/*.json");
+        let files = pattern
+            .to_str()
+            .and_then(|pattern| glob::glob(pattern).ok())
+            .into_iter()
+            .flatten()
+            .filter_map(|file| file.ok());
+        files
+            .filter_map(|file| read_build_info_layouts(&file).ok())
+            .flatten()
+            .collect()
+    }
+
     fn should_exclude_file(&self, path: &Path) -> bool {
         path.to_str().is_none_or(|f| f.contains(".dbg.json"))
     }
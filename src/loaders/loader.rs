@@ -1,5 +1,7 @@
 use alloy::json_abi::JsonAbi;
 use anyhow::{anyhow, Result};
+
+use super::types::{Project, StorageLayout};
 use serde_json::Value;
 use std::{
     collections::HashMap,
@@ -16,18 +18,32 @@ pub trait ProjectLoader {
     fn should_exclude_file(&self, path: &Path) -> bool;
     fn abi_dirs(&self) -> Vec<PathBuf>;
 
-    fn load_abi_from_file(&self, filepath: &Path) -> Result<(String, JsonAbi)> {
+    /// Loads the ABI of the artifact, as well as its storage layout when it was output by the compiler
+    fn load_abi_from_file(
+        &self,
+        filepath: &Path,
+    ) -> Result<(String, JsonAbi, Option<StorageLayout>)> {
         let file = File::open(filepath)?;
         let reader = BufReader::new(file);
         let json: Value = serde_json::from_reader(reader)?;
         let contract_name = self.get_contract_name(&json)?;
+        let storage_layout = match &json["storageLayout"] {
+            Value::Null => None,
+            layout => StorageLayout::try_from(layout).ok(),
+        };
         Ok((
             contract_name.to_string(),
             JsonAbi::from_json_str(&json["abi"].to_string())?,
             // serde_json::from_value(json["abi"].clone())?, // TODO: figure out why this doesn't work
+            storage_layout,
         ))
     }
 
+    /// Loads the storage layouts stored outside of the artifacts, by contract name
+    fn load_storage_layouts(&self, _directory: &Path) -> HashMap<String, StorageLayout> {
+        HashMap::new()
+    }
+
     fn get_abi_files(&self, directory: &Path) -> Result<Vec<String>> {
         let files = glob::glob(Path::new(directory).join("**/*.json").to_str().unwrap())?;
         let mut result = vec![];
@@ -42,18 +58,29 @@ pub trait ProjectLoader {
         Ok(result)
     }
 
-    fn load(&self, directory: &Path) -> Result<HashMap<String, JsonAbi>> {
+    fn load(&self, directory: &Path) -> Result<Project> {
         if !self.is_valid(directory) {
             return Err(anyhow::anyhow!("Invalid project"));
         }
         let mut abis = HashMap::new();
+        let mut storage_layouts = HashMap::new();
         for abi_dir in self.abi_dirs() {
             for filepath in self.get_abi_files(&directory.join(abi_dir))? {
-                if let Ok((contract_name, abi)) = self.load_abi_from_file(Path::new(&filepath)) {
+                if let Ok((contract_name, abi, storage_layout)) =
+                    self.load_abi_from_file(Path::new(&filepath))
+                {
+                    if let Some(layout) = storage_layout {
+                        storage_layouts.insert(contract_name.clone(), layout);
+                    }
                     abis.insert(contract_name, abi);
                 }
             }
         }
-        Ok(abis)
+        for (contract_name, layout) in self.load_storage_layouts(directory) {
+            if abis.contains_key(&contract_name) {
+                storage_layouts.entry(contract_name).or_insert(layout);
+            }
+        }
+        Ok(Project::new(abis, storage_layouts))
     }
 }
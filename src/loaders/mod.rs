@@ -23,7 +23,7 @@ pub fn load<P: AsRef<std::path::Path>>(directory: P) -> Vec<types::Project> {
     for loader in loaders.iter() {
         if loader.is_valid(directory.as_ref()) {
             match loader.load(directory.as_ref()) {
-                Ok(abis) => projects.push(types::Project::new(abis)),
+                Ok(project) => projects.push(project),
                 Err(e) => eprintln!("Error loading {} project: {:?}", loader.name(), e),
             }
         }
@@ -1,19 +1,116 @@
 use alloy::json_abi::JsonAbi;
+use anyhow::{anyhow, Result};
+use serde_json::Value;
 use std::collections::HashMap;
 
+/// Variable of a storage layout, or member of a struct, as output by solc
+#[derive(Debug, Clone)]
+pub struct StorageEntry {
+    pub label: String,
+    pub slot: String,
+    pub offset: usize,
+    pub type_id: String,
+}
+
+/// Type of a storage layout, as output by solc
+#[derive(Debug, Clone)]
+pub struct StorageType {
+    pub encoding: String,
+    pub label: String,
+    pub number_of_bytes: usize,
+    pub key: Option<String>,
+    pub value: Option<String>,
+    pub base: Option<String>,
+    pub members: Option<Vec<StorageEntry>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StorageLayout {
+    pub storage: Vec<StorageEntry>,
+    pub types: HashMap<String, StorageType>,
+}
+
+fn get_str(json: &Value, key: &str) -> Result<String> {
+    json[key]
+        .as_str()
+        .map(|s| s.to_string())
+        .ok_or(anyhow!("invalid storage layout: missing {}", key))
+}
+
+fn parse_entries(json: &Value) -> Result<Vec<StorageEntry>> {
+    let entries = json
+        .as_array()
+        .ok_or(anyhow!("invalid storage layout: expected array"))?;
+    entries
+        .iter()
+        .map(|entry| {
+            Ok(StorageEntry {
+                label: get_str(entry, "label")?,
+                slot: get_str(entry, "slot")?,
+                offset: entry["offset"]
+                    .as_u64()
+                    .ok_or(anyhow!("invalid storage layout: missing offset"))?
+                    as usize,
+                type_id: get_str(entry, "type")?,
+            })
+        })
+        .collect()
+}
+
+impl TryFrom<&Value> for StorageLayout {
+    type Error = anyhow::Error;
+
+    fn try_from(json: &Value) -> Result<Self> {
+        let storage = parse_entries(&json["storage"])?;
+        let mut types = HashMap::new();
+        // solc outputs null instead of an empty object when there are no variables
+        if let Some(types_json) = json["types"].as_object() {
+            for (type_id, type_json) in types_json {
+                let members = match &type_json["members"] {
+                    Value::Null => None,
+                    members => Some(parse_entries(members)?),
+                };
+                let optional = |key: &str| type_json[key].as_str().map(|s| s.to_string());
+                let type_ = StorageType {
+                    encoding: get_str(type_json, "encoding")?,
+                    label: get_str(type_json, "label")?,
+                    number_of_bytes: get_str(type_json, "numberOfBytes")?.parse()?,
+                    key: optional("key"),
+                    value: optional("value"),
+                    base: optional("base"),
+                    members,
+                };
+                types.insert(type_id.clone(), type_);
+            }
+        }
+        Ok(StorageLayout { storage, types })
+    }
+}
+
 pub struct Project {
     abis: HashMap<String, JsonAbi>,
+    storage_layouts: HashMap<String, StorageLayout>,
 }
 
 impl Project {
-    pub fn new(abis: HashMap<String, JsonAbi>) -> Self {
-        Project { abis }
+    pub fn new(
+        abis: HashMap<String, JsonAbi>,
+        storage_layouts: HashMap<String, StorageLayout>,
+    ) -> Self {
+        Project {
+            abis,
+            storage_layouts,
+        }
     }
 
     pub fn get_contract(&self, name: &str) -> JsonAbi {
         self.abis.get(name).expect("Contract not found").clone()
     }
 
+    pub fn get_storage_layout(&self, name: &str) -> Option<&StorageLayout> {
+        self.storage_layouts.get(name)
+    }
+
     pub fn contract_names(&self) -> Vec<String> {
         self.abis.keys().cloned().collect()
     }
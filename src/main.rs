@@ -22,10 +22,21 @@ async fn main() -> Result<()> {
 
     let foundry_conf = foundry_config::load_config().map_err(anyhow::Error::msg)?;
 
-    let config = Config::new(cli.rpc_url.clone(), cli.debug, foundry_conf);
+    let mut config = Config::new(cli.rpc_url.clone(), cli.debug, foundry_conf);
+    config.log_rpc = cli.log_rpc;
+    config.requests_per_second = cli.requests_per_second;
+    config.rpc_cache = cli.rpc_cache;
+    config.record = cli.record.clone();
+    config.replay = cli.replay.clone();
+    if let Some(max_retries) = cli.max_retries {
+        config.max_retries = max_retries;
+    }
+    if let Some(cups) = cli.compute_units_per_second {
+        config.compute_units_per_second = cups;
+    }
 
     if let Some(script_file) = cli.script_file.as_ref() {
-        let mut env = Env::new(config);
+        let mut env = Env::connect(config).await?;
         initialize_env(&mut env, &cli.init_file_name).await?;
         let code = std::fs::read_to_string(script_file)?;
         let result = interpreter::evaluate_code(&mut env, &code).await?;
@@ -36,7 +47,7 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    let env = Arc::new(Mutex::new(Env::new(config)));
+    let env = Arc::new(Mutex::new(Env::connect(config).await?));
     let mut repl = Repl::create(env, &cli).await?;
     repl.run().await;
 
@@ -23,6 +23,34 @@ pub struct Cli {
     #[arg(long, value_name = "FILE_NAME", env = "INIT_FILE_NAME")]
     pub init_file_name: Option<PathBuf>,
 
+    /// Log every JSON-RPC request with its latency and payload size
+    #[arg(long, env = "ECLAIR_LOG_RPC")]
+    pub log_rpc: bool,
+
+    /// Maximum number of retries on rate limits and transient RPC errors
+    #[arg(long, value_name = "RETRIES", env = "ECLAIR_MAX_RETRIES")]
+    pub max_retries: Option<u32>,
+
+    /// Compute units per second allowed by the RPC provider, used to compute retry backoffs
+    #[arg(long, value_name = "CUPS", env = "ECLAIR_COMPUTE_UNITS_PER_SECOND")]
+    pub compute_units_per_second: Option<u64>,
+
+    /// Maximum number of RPC requests to send per second
+    #[arg(long, value_name = "RPS", env = "ECLAIR_REQUESTS_PER_SECOND")]
+    pub requests_per_second: Option<u32>,
+
+    /// Cache responses of RPC reads at a fixed block on disk
+    #[arg(long, env = "ECLAIR_RPC_CACHE")]
+    pub rpc_cache: bool,
+
+    /// Record the RPC requests and their responses to the given file
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Serve RPC requests from a file written with --record instead of the network
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+
     /// File to execute instead of starting the REPL
     #[arg(value_name = "FILE")]
     pub script_file: Option<PathBuf>,
@@ -16,6 +16,9 @@ pub async fn initialize_env(env: &mut Env, init_file_name: &Option<PathBuf>) ->
     let current_dir = std::env::current_dir()?;
     let projects = loaders::load(current_dir);
     interpreter::load_builtins(env);
+    if let Err(e) = env.load_labels().await {
+        eprintln!("warning: could not load labels: {}", e);
+    }
     for project in projects.iter() {
         interpreter::load_project(env, project)?;
     }
@@ -103,7 +106,7 @@ impl Repl {
         let mut env = self.env.lock().await;
         match interpreter::evaluate_code(&mut env, line.trim()).await {
             Ok(None) | Ok(Some(interpreter::Value::Null)) => (),
-            Ok(Some(result)) => println!("{}", result),
+            Ok(Some(result)) => println!("{}", env.format_value(&result)),
             Err(e) => println!("Error: {:?}", e),
         }
     }
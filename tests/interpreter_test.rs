@@ -1,4 +1,41 @@
-use eclair::interpreter::{self, Config, Env, Type, Value};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use alloy::{
+    consensus::{
+        transaction::Recovered, SignableTransaction, Transaction as _, TxEip1559, TxEnvelope,
+        TxType,
+    },
+    dyn_abi::TypedData,
+    eips::{Decodable2718, Encodable2718},
+    json_abi::JsonAbi,
+    network::TxSignerSync,
+    primitives::{address, b256, hex, keccak256, Address, Bytes, FixedBytes, TxKind, B256, U256},
+    rpc::{
+        client::RpcClient,
+        json_rpc::ErrorPayload,
+        types::{FeeHistory, Transaction as RpcTransaction, TransactionRequest},
+    },
+    signers::{local::PrivateKeySigner, SignerSync},
+    sol,
+    sol_types::{SolError, SolValue},
+    transports::mock::{Asserter, MockTransport},
+};
+use eclair::{
+    interpreter::{self, Config, Env, RecordLayer, Recorder, Type, Value},
+    loaders::types::{Project, StorageLayout},
+};
+use tower::Layer;
+
+sol! {
+    error OffchainLookup(address sender, string[] urls, bytes callData, bytes4 callbackFunction, bytes extraData);
+}
 
 #[tokio::test]
 async fn test_binops() {
@@ -133,6 +170,791 @@ async fn test_for_loop() {
     assert_eq!(res, Some(Value::from(15u64)));
 }
 
+#[tokio::test]
+async fn test_mocked_client() {
+    let (mut env, asserter) = _create_mocked_env();
+
+    asserter.push_success(&U256::from(42));
+    _check_result(
+        &mut env,
+        "address(0x0000000000000000000000000000000000000001).balance",
+        Value::Uint(U256::from(42), 256),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_ens_resolve() {
+    let (mut env, asserter, recording) = _create_recording_env("ens-resolve");
+
+    let resolver = address!("231b0Ee14048e9dCcD1d247744d114a4EB5E8E63");
+    let vitalik = address!("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+    for code in ["ens.resolve(\"Vitalik.eth\")", "address(\"vitalik.eth\")"] {
+        asserter.push_success(&_universal_resolver_result(vitalik, resolver));
+        _check_result(&mut env, code, Value::Addr(vitalik)).await;
+    }
+
+    // the name is resolved through the UniversalResolver, DNS-encoded and normalized
+    let params = _recorded_params(&recording, "eth_call");
+    assert_eq!(
+        params[0]["to"],
+        "0xeeeeeeee14d718c2b47d9923deab1335e144eeee"
+    );
+    let node = b256!("ee6c4522aab0003e8d14cd40a6af439055fd2577951148c14b6cea9a53475835");
+    let addr_call = [hex!("3b3b57de").as_slice(), node.as_slice()].concat();
+    let resolve_call = [
+        hex!("9061b923").as_slice(),
+        &(
+            Bytes::from_static(b"\x07vitalik\x03eth\x00"),
+            Bytes::from(addr_call),
+        )
+            .abi_encode_params(),
+    ]
+    .concat();
+    assert_eq!(params[0]["input"], hex::encode_prefixed(resolve_call));
+
+    assert!(asserter.read_q().is_empty());
+    fs::remove_file(recording).unwrap();
+}
+
+#[tokio::test]
+async fn test_ens_offchain_resolve() {
+    let (mut env, asserter, recording) = _create_recording_env("ens-offchain-resolve");
+
+    let resolver = address!("231b0Ee14048e9dCcD1d247744d114a4EB5E8E63");
+    let vitalik = address!("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+    let gateway_data = Bytes::from_static(b"gateway response");
+    let url = _start_gateway_server(gateway_data.clone());
+    let lookup = OffchainLookup {
+        sender: address!("eEeEEEeE14D718C2B47D9923Deab1335E144EeEe"),
+        urls: vec![format!("{}/{{sender}}/{{data}}.json", url)],
+        callData: Bytes::from_static(b"call data"),
+        callbackFunction: FixedBytes::from(hex!("12345678")),
+        extraData: Bytes::from_static(b"extra data"),
+    };
+    asserter.push_failure(ErrorPayload {
+        code: 3,
+        message: "execution reverted".into(),
+        data: Some(
+            serde_json::value::to_raw_value(&hex::encode_prefixed(lookup.abi_encode())).unwrap(),
+        ),
+    });
+    asserter.push_success(&_universal_resolver_result(vitalik, resolver));
+    _check_result(
+        &mut env,
+        "ens.resolve(\"offchain.vitalik.eth\")",
+        Value::Addr(vitalik),
+    )
+    .await;
+
+    // the gateway response is passed to the callback along with the extra data
+    let callback_call = [
+        hex!("12345678").as_slice(),
+        &(gateway_data, lookup.extraData).abi_encode_params(),
+    ]
+    .concat();
+    assert_eq!(
+        _recorded_params(&recording, "eth_call")[0]["input"],
+        hex::encode_prefixed(callback_call)
+    );
+
+    assert!(asserter.read_q().is_empty());
+    fs::remove_file(recording).unwrap();
+}
+
+#[tokio::test]
+async fn test_replay() {
+    let foundry_conf = foundry_config::load_config().unwrap();
+    let mut config = Config::new(None, false, foundry_conf);
+    config.replay = Some(PathBuf::from("tests/fixtures/balance_recording.json"));
+    let mut env = Env::connect(config).await.unwrap();
+    interpreter::load_builtins(&mut env);
+
+    _check_result(
+        &mut env,
+        "address(0x0000000000000000000000000000000000000001).balance",
+        Value::Uint(U256::from(10).pow(U256::from(18)), 256),
+    )
+    .await;
+
+    // signature lookups are replayed as well, without querying 4byte.directory
+    let foundry_conf = foundry_config::load_config().unwrap();
+    let mut config = Config::new(None, false, foundry_conf);
+    config.replay = Some(PathBuf::from("tests/fixtures/signature_recording.json"));
+    let mut env = Env::new(config).unwrap();
+    interpreter::load_builtins(&mut env);
+
+    _check_result(
+        &mut env,
+        "abi.getSignature(0x1bcf634e)",
+        Value::Str("executeL2Proposal()".to_string()),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_events_fetch_unknown_option() {
+    let mut env = _create_env();
+
+    let res = interpreter::evaluate_code(
+        &mut env,
+        "events.fetch{fromblock: 1}(0x0000000000000000000000000000000000000001)",
+    )
+    .await;
+    assert_eq!(res.unwrap_err().to_string(), "unexpected key fromblock");
+}
+
+#[tokio::test]
+async fn test_events_fetch_failed_chunk() {
+    let (mut env, asserter) = _create_mocked_env();
+
+    asserter.push_success(&serde_json::json!([]));
+    asserter.push_failure_msg("internal error");
+    let res = interpreter::evaluate_code(
+        &mut env,
+        "events.fetch{fromBlock: 1, toBlock: 2, chunkSize: 1}(0x0000000000000000000000000000000000000001)",
+    )
+    .await;
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("resume with {fromBlock: 2}"));
+}
+
+#[tokio::test]
+async fn test_storage_keyword() {
+    let (mut env, asserter) = _create_mocked_env();
+    let layout = StorageLayout::try_from(&serde_json::json!({
+        "storage": [{"label": "totalShares", "slot": "0", "offset": 0, "type": "t_uint256"}],
+        "types": {
+            "t_uint256": {"encoding": "inplace", "label": "uint256", "numberOfBytes": "32"}
+        }
+    }))
+    .unwrap();
+    let project = Project::new(
+        HashMap::from([("Vault".to_string(), JsonAbi::default())]),
+        HashMap::from([("Vault".to_string(), layout)]),
+    );
+    interpreter::load_project(&mut env, &project).unwrap();
+
+    asserter.push_success(&U256::from(42));
+    _check_result(
+        &mut env,
+        "Vault(0x0000000000000000000000000000000000000001).storage.totalShares",
+        Value::from(42u64),
+    )
+    .await;
+    _check_result(
+        &mut env,
+        "storage.erc7201(\"openzeppelin.storage.ERC20\")",
+        Value::FixBytes(
+            b256!("52c63247e1f47db19d5ce0460030c497f067ca4cebf71ba98eeadabe20bace00"),
+            32,
+        ),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_sign_message() {
+    let mut env = _create_env();
+
+    _execute(
+        &mut env,
+        "accounts.loadPrivateKey(0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80)",
+    )
+    .await;
+    _execute(&mut env, "signature = accounts.signMessage(\"hello\")").await;
+    _check_result(&mut env, "signature.length", Value::from(65u64)).await;
+    _execute(&mut env, "parsed = sig.parse(signature)").await;
+    _check_result(
+        &mut env,
+        "parsed.v == 27 || parsed.v == 28",
+        Value::Bool(true),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_recover_signature() {
+    let mut env = _create_env();
+    let signer = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+    _execute(
+        &mut env,
+        "accounts.loadPrivateKey(0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80)",
+    )
+    .await;
+    _execute(&mut env, "signature = accounts.signMessage(\"hello\")").await;
+    // "\x19Ethereum Signed Message:\n5hello"
+    _execute(
+        &mut env,
+        "hash = keccak256(0x19457468657265756d205369676e6564204d6573736167653a0a3568656c6c6f)",
+    )
+    .await;
+    _check_result(
+        &mut env,
+        &format!("sig.recover(hash, signature) == {}", signer),
+        Value::Bool(true),
+    )
+    .await;
+    _execute(&mut env, "(v, r, s) = sig.split(signature)").await;
+    _check_result(
+        &mut env,
+        &format!("ecrecover(hash, v, r, s) == {}", signer),
+        Value::Bool(true),
+    )
+    .await;
+    _check_result(
+        &mut env,
+        "sig.join(v, r, s) == signature",
+        Value::Bool(true),
+    )
+    .await;
+    _check_result(&mut env, "sig.parse(signature).s == s", Value::Bool(true)).await;
+}
+
+#[tokio::test]
+async fn test_load_mnemonic() {
+    let mut env = _create_env();
+    let mnemonic = "test test test test test test test test test test test junk";
+
+    _check_result(
+        &mut env,
+        &format!("accounts.listMnemonicWallets(\"{}\", 2)[1]", mnemonic),
+        Value::Addr(address!("70997970C51812dc3A010C7d01b50e0d17dc79C8")),
+    )
+    .await;
+    _check_result(
+        &mut env,
+        &format!(
+            "accounts.loadMnemonic(\"{}\", \"m/44'/60'/0'/0/1\")",
+            mnemonic
+        ),
+        Value::Addr(address!("70997970C51812dc3A010C7d01b50e0d17dc79C8")),
+    )
+    .await;
+    _check_result(
+        &mut env,
+        &format!("accounts.loadMnemonic(\"{}\", 0)", mnemonic),
+        Value::Addr(address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266")),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_sign_authorization() {
+    let mut env = _create_env();
+
+    _execute(
+        &mut env,
+        "accounts.loadPrivateKey(0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80)",
+    )
+    .await;
+    _execute(
+        &mut env,
+        "auth = accounts.signAuthorization(0x63c0c19a282a1B52b07dD5a65b58948A07DAE32B, 3, 1)",
+    )
+    .await;
+    _check_result(
+        &mut env,
+        "auth.address",
+        Value::Addr(address!("63c0c19a282a1B52b07dD5a65b58948A07DAE32B")),
+    )
+    .await;
+    _check_result(&mut env, "auth.nonce", Value::Uint(U256::from(3), 64)).await;
+    _check_result(&mut env, "auth.chainId", Value::from(1u64)).await;
+
+    let auth = _execute(&mut env, "auth").await.unwrap();
+    let authority = auth.as_authorization().unwrap().recover_authority();
+    assert_eq!(
+        authority.unwrap(),
+        address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266")
+    );
+}
+
+#[tokio::test]
+async fn test_sign_authorization_default_nonce() {
+    let (mut env, asserter, recording) = _create_recording_env("sign-authorization");
+    _execute(
+        &mut env,
+        "accounts.loadPrivateKey(0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80)",
+    )
+    .await;
+
+    // the current account sends the transaction, incrementing its nonce first
+    asserter.push_success(&"0x5");
+    asserter.push_success(&"0x1");
+    _execute(
+        &mut env,
+        "auth = accounts.signAuthorization(0x63c0c19a282a1B52b07dD5a65b58948A07DAE32B)",
+    )
+    .await;
+    _check_result(&mut env, "auth.nonce", Value::Uint(U256::from(6), 64)).await;
+    _check_result(&mut env, "auth.chainId", Value::from(1u64)).await;
+    assert_eq!(
+        _recorded_params(&recording, "eth_getTransactionCount"),
+        serde_json::json!(["0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266", "pending"])
+    );
+
+    asserter.push_success(&"0x5");
+    _execute(
+        &mut env,
+        "auth = accounts.signAuthorization{sponsored: true}(0x63c0c19a282a1B52b07dD5a65b58948A07DAE32B, null, 1)",
+    )
+    .await;
+    _check_result(&mut env, "auth.nonce", Value::Uint(U256::from(5), 64)).await;
+
+    let res = interpreter::evaluate_code(
+        &mut env,
+        "accounts.signAuthorization{gas: 1}(0x63c0c19a282a1B52b07dD5a65b58948A07DAE32B, 1)",
+    )
+    .await;
+    assert!(format!("{:#}", res.unwrap_err()).contains("unexpected key gas"));
+
+    assert!(asserter.read_q().is_empty());
+    fs::remove_file(recording).unwrap();
+}
+
+#[tokio::test]
+async fn test_remote_signer() {
+    let mut env = _create_env();
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    let url = _start_signer_server(private_key.parse().unwrap(), SignedTxResponse::Raw);
+
+    _check_result(
+        &mut env,
+        &format!(
+            "accounts.loadRemoteSigner(\"{}\", 0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266)",
+            url
+        ),
+        Value::Addr(address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266")),
+    )
+    .await;
+    _execute(
+        &mut env,
+        "remoteSignature = accounts.signMessage(\"hello\")",
+    )
+    .await;
+    _execute(
+        &mut env,
+        &format!(
+            "remoteTypedSignature = accounts.signTypedData('{}')",
+            TYPED_DATA
+        ),
+    )
+    .await;
+    _execute(
+        &mut env,
+        &format!("accounts.loadPrivateKey({})", private_key),
+    )
+    .await;
+    _check_result(
+        &mut env,
+        "remoteSignature == accounts.signMessage(\"hello\")",
+        Value::Bool(true),
+    )
+    .await;
+    _check_result(
+        &mut env,
+        &format!(
+            "remoteTypedSignature == accounts.signTypedData('{}')",
+            TYPED_DATA
+        ),
+        Value::Bool(true),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_remote_signer_transaction() {
+    let private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    let sign_tx = "Transaction.sign{gasLimit: 21000, maxFee: 2, priorityFee: 1, nonce: 0}(0x0000000000000000000000000000000000000002, 0x)";
+
+    for tx_response in [SignedTxResponse::Raw, SignedTxResponse::Object] {
+        let (mut env, asserter) = _create_mocked_env();
+        let url = _start_signer_server(private_key.parse().unwrap(), tx_response);
+        _execute(
+            &mut env,
+            &format!(
+                "accounts.loadRemoteSigner(\"{}\", 0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266)",
+                url
+            ),
+        )
+        .await;
+        asserter.push_success(&"0x1");
+        let remote_tx = _execute(&mut env, sign_tx).await;
+
+        _execute(
+            &mut env,
+            &format!("accounts.loadPrivateKey({})", private_key),
+        )
+        .await;
+        asserter.push_success(&"0x1");
+        assert_eq!(remote_tx, _execute(&mut env, sign_tx).await);
+    }
+
+    let (mut env, asserter) = _create_mocked_env();
+    let url = _start_signer_server(private_key.parse().unwrap(), SignedTxResponse::WrongNonce);
+    _execute(
+        &mut env,
+        &format!(
+            "accounts.loadRemoteSigner(\"{}\", 0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266)",
+            url
+        ),
+    )
+    .await;
+    asserter.push_success(&"0x1");
+    let res = interpreter::evaluate_code(&mut env, sign_tx).await;
+    assert!(
+        format!("{:#}", res.unwrap_err()).contains("remote signer signed a different transaction")
+    );
+}
+
+#[tokio::test]
+async fn test_sign_and_send_transaction() {
+    let (mut env, asserter) = _create_mocked_env();
+    let to = address!("0000000000000000000000000000000000000002");
+    _execute(
+        &mut env,
+        "accounts.loadPrivateKey(0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80)",
+    )
+    .await;
+
+    // signing does not reserve the nonce, so both transactions use the pending nonce
+    for i in 0..2 {
+        asserter.push_success(&"0x5"); // pending transaction count
+        if i == 0 {
+            asserter.push_success(&"0x1"); // chain id, cached by the provider
+        }
+        let tx = _decode_tx(
+            _execute(
+                &mut env,
+                "Transaction.sign{gasLimit: 21000, maxFee: 2, priorityFee: 1}(0x0000000000000000000000000000000000000002, 0x1234)",
+            )
+            .await,
+        );
+        assert_eq!(tx.tx_type(), TxType::Eip1559);
+        assert_eq!(tx.nonce(), 5);
+        assert_eq!(tx.to(), Some(to));
+        assert_eq!(tx.input().as_ref(), [0x12, 0x34]);
+    }
+
+    _execute(
+        &mut env,
+        "raw = Transaction.sign{type: \"legacy\", gasLimit: 21000, gasPrice: 3, nonce: 7}(0x0000000000000000000000000000000000000002, 0x)",
+    )
+    .await;
+    let raw = _execute(&mut env, "raw").await;
+    let tx = _decode_tx(raw.clone());
+    assert_eq!(tx.tx_type(), TxType::Legacy);
+    assert_eq!(tx.nonce(), 7);
+    assert_eq!(tx.gas_price(), Some(3));
+
+    let res = interpreter::evaluate_code(
+        &mut env,
+        "Transaction.sign{type: 3}(0x0000000000000000000000000000000000000002, 0x)",
+    )
+    .await;
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("unsupported transaction type 3"));
+
+    let hash = match raw {
+        Some(Value::Bytes(raw)) => keccak256(raw),
+        _ => unreachable!(),
+    };
+    asserter.push_success(&hash);
+    _check_result(
+        &mut env,
+        "Transaction.sendRaw(raw)",
+        Value::Transaction(hash, None),
+    )
+    .await;
+
+    asserter.push_success(&hash);
+    _check_result(
+        &mut env,
+        "Transaction.send{type: \"legacy\", gasLimit: 21000, gasPrice: 3, nonce: 7}(0x0000000000000000000000000000000000000002, 0x)",
+        Value::Transaction(hash, None),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_replace_transaction() {
+    let (mut env, asserter, recording) = _create_recording_env("replace-transaction");
+    let signer = PrivateKeySigner::from_bytes(&b256!(
+        "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+    ))
+    .unwrap();
+    let from = signer.address();
+    let to = address!("0000000000000000000000000000000000000002");
+    _execute(
+        &mut env,
+        "accounts.loadPrivateKey(0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80)",
+    )
+    .await;
+
+    let (hash, speed_up_hash, cancel_hash, zero_tip_hash, next_hash) = (
+        B256::repeat_byte(1),
+        B256::repeat_byte(2),
+        B256::repeat_byte(3),
+        B256::repeat_byte(4),
+        B256::repeat_byte(5),
+    );
+    asserter.push_success(&"0x1"); // chain id
+    asserter.push_success(&hash);
+    _execute(
+        &mut env,
+        "tx = Transaction.send{gasLimit: 21000, maxFee: 100, priorityFee: 10, nonce: 3}(0x0000000000000000000000000000000000000002, 0x1234)",
+    )
+    .await;
+    let sent = _last_sent_tx(&recording);
+    assert_eq!(sent.nonce(), 3);
+
+    // the replacement reuses the nonce of the pending transaction with bumped fees,
+    // raised to the current estimate when it is higher
+    asserter.push_success(&_rpc_tx(sent.clone(), from, None));
+    asserter.push_success(&_estimation_history(100, 2));
+    asserter.push_success(&speed_up_hash);
+    _execute(&mut env, "tx = tx.speedUp(1.5)").await;
+    _check_result(&mut env, "tx", Value::Transaction(speed_up_hash, None)).await;
+    let replacement = _last_sent_tx(&recording);
+    assert_eq!(replacement.nonce(), 3);
+    assert_eq!(replacement.max_fee_per_gas(), 202);
+    assert_eq!(replacement.max_priority_fee_per_gas(), Some(15));
+    assert_eq!(replacement.to(), Some(to));
+    assert_eq!(replacement.input().as_ref(), [0x12, 0x34]);
+
+    asserter.push_success(&_rpc_tx(replacement.clone(), from, None));
+    asserter.push_success(&_estimation_history(50, 2));
+    asserter.push_success(&"0x5208"); // gas estimate
+    asserter.push_success(&cancel_hash);
+    _execute(&mut env, "tx = tx.cancel()").await;
+    _check_result(&mut env, "tx", Value::Transaction(cancel_hash, None)).await;
+    let cancellation = _last_sent_tx(&recording);
+    assert_eq!(cancellation.nonce(), 3);
+    assert_eq!(cancellation.max_fee_per_gas(), 242);
+    assert_eq!(cancellation.max_priority_fee_per_gas(), Some(18));
+    assert_eq!(cancellation.to(), Some(from));
+    assert_eq!(cancellation.value(), U256::ZERO);
+    assert!(cancellation.input().is_empty());
+
+    let res = interpreter::evaluate_code(&mut env, "tx.speedUp(1.05)").await;
+    assert!(res.unwrap_err().to_string().contains("at least 10%"));
+
+    let signed_tx = |max_fee_per_gas: u128, max_priority_fee_per_gas: u128| {
+        let mut tx = TxEip1559 {
+            chain_id: 1,
+            nonce: 3,
+            gas_limit: 21000,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            to: TxKind::Call(to),
+            ..Default::default()
+        };
+        let signature = signer.sign_transaction_sync(&mut tx).unwrap();
+        TxEnvelope::from(tx.into_signed(signature))
+    };
+    asserter.push_success(&_rpc_tx(signed_tx(u128::MAX / 2, 1), from, None));
+    asserter.push_success(&_estimation_history(50, 2));
+    let res = interpreter::evaluate_code(&mut env, "tx.speedUp()").await;
+    assert!(res.unwrap_err().to_string().contains("fee bump overflows"));
+
+    // a zero priority fee is still increased
+    let zero_tip_tx = signed_tx(100, 0);
+    asserter.push_success(&_rpc_tx(zero_tip_tx.clone(), from, None));
+    asserter.push_success(&_estimation_history(50, 0));
+    asserter.push_success(&zero_tip_hash);
+    _execute(&mut env, "tx = tx.speedUp(1.1)").await;
+    let replacement = _last_sent_tx(&recording);
+    assert_eq!(replacement.max_fee_per_gas(), 110);
+    assert_eq!(replacement.max_priority_fee_per_gas(), Some(1));
+
+    asserter.push_success(&next_hash);
+    _execute(
+        &mut env,
+        "Transaction.send{gasLimit: 21000, maxFee: 100, priorityFee: 10, nonce: 4}(0x0000000000000000000000000000000000000002, 0x)",
+    )
+    .await;
+    let next = _last_sent_tx(&recording);
+    assert_eq!(next.nonce(), 4);
+
+    // mined transactions are untracked and not queried anymore
+    asserter.push_success(&_rpc_tx(zero_tip_tx, from, Some(1)));
+    asserter.push_success(&_rpc_tx(next, from, None));
+    _check_result(
+        &mut env,
+        "accounts.pending()",
+        Value::Array(
+            vec![Value::Transaction(next_hash, None)],
+            Box::new(Type::Transaction),
+        ),
+    )
+    .await;
+    asserter.push_success(&serde_json::Value::Null);
+    _check_result(
+        &mut env,
+        "accounts.pending()",
+        Value::Array(vec![], Box::new(Type::Transaction)),
+    )
+    .await;
+    assert!(asserter.read_q().is_empty());
+    fs::remove_file(recording).unwrap();
+}
+
+#[tokio::test]
+async fn test_fee_strategy() {
+    let (mut env, asserter, recording) = _create_recording_env("fee-strategy");
+    _execute(
+        &mut env,
+        "accounts.loadPrivateKey(0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80)",
+    )
+    .await;
+    _execute(
+        &mut env,
+        "config = repl.fees{strategy: \"fast\", maxFeeCap: 1000}()",
+    )
+    .await;
+    _check_result(&mut env, "config.strategy", Value::from("fast")).await;
+    _check_result(&mut env, "config.maxFeeCap", Value::from(1000u64)).await;
+    _check_result(&mut env, "config.gasLimitMultiplier", Value::Null).await;
+
+    // the base fee of the next block and the median of the non-zero rewards are used
+    asserter.push_success(&_fee_history());
+    asserter.push_success(&"0x0"); // pending transaction count
+    asserter.push_success(&"0x1"); // chain id
+    let tx = _decode_tx(
+        _execute(
+            &mut env,
+            "Transaction.sign{gasLimit: 21000}(0x0000000000000000000000000000000000000002, 0x)",
+        )
+        .await,
+    );
+    assert_eq!(tx.max_fee_per_gas(), 207);
+    assert_eq!(tx.max_priority_fee_per_gas(), Some(7));
+    assert_eq!(
+        _recorded_params(&recording, "eth_feeHistory"),
+        serde_json::json!(["0xa", "latest", [90.0]])
+    );
+
+    // the estimated priority fee is clamped to an explicit max fee
+    asserter.push_success(&_fee_history());
+    asserter.push_success(&"0x0");
+    let tx = _decode_tx(
+        _execute(
+            &mut env,
+            "Transaction.sign{gasLimit: 21000, maxFee: 5}(0x0000000000000000000000000000000000000002, 0x)",
+        )
+        .await,
+    );
+    assert_eq!(tx.max_fee_per_gas(), 5);
+    assert_eq!(tx.max_priority_fee_per_gas(), Some(5));
+
+    let res = interpreter::evaluate_code(
+        &mut env,
+        "Transaction.sign{gasLimit: 21000, maxFee: 2000, priorityFee: 1}(0x0000000000000000000000000000000000000002, 0x)",
+    )
+    .await;
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        "transaction fee (2000 wei) is above the max fee cap (1000 wei)"
+    );
+
+    _execute(&mut env, "repl.fees{maxFeeCap: 100}()").await;
+    asserter.push_success(&_fee_history());
+    let res = interpreter::evaluate_code(
+        &mut env,
+        "Transaction.sign{gasLimit: 21000}(0x0000000000000000000000000000000000000002, 0x)",
+    )
+    .await;
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        "current fees (107 wei) are above the max fee cap (100 wei)"
+    );
+
+    // the gas limit is only estimated when not set explicitly
+    _execute(
+        &mut env,
+        "config = repl.fees{maxFeeCap: null, gasLimitMultiplier: 1.2}()",
+    )
+    .await;
+    _check_result(
+        &mut env,
+        "config.gasLimitMultiplier",
+        Value::Decimal(U256::from(12), 1),
+    )
+    .await;
+    asserter.push_success(&"0x5208"); // gas estimate
+    asserter.push_success(&_fee_history());
+    asserter.push_success(&"0x0");
+    let tx = _decode_tx(
+        _execute(
+            &mut env,
+            "Transaction.sign(0x0000000000000000000000000000000000000002, 0x)",
+        )
+        .await,
+    );
+    assert_eq!(tx.gas_limit(), 25200);
+    let res = interpreter::evaluate_code(&mut env, "repl.fees{gasLimitMultiplier: 0.5}()").await;
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        "gasLimitMultiplier must be at least 1"
+    );
+
+    _execute(
+        &mut env,
+        "config = repl.fees{strategy: null, gasLimitMultiplier: null}()",
+    )
+    .await;
+    _check_result(&mut env, "config.strategy", Value::Null).await;
+    _check_result(&mut env, "config.maxFeeCap", Value::Null).await;
+    _check_result(&mut env, "config.gasLimitMultiplier", Value::Null).await;
+    assert!(asserter.read_q().is_empty());
+    fs::remove_file(recording).unwrap();
+}
+
+#[tokio::test]
+async fn test_fee_history() {
+    let (mut env, asserter, recording) = _create_recording_env("fee-history");
+    let uint_array = |values: &[u64]| {
+        Value::Array(
+            values.iter().map(|v| Value::from(*v)).collect(),
+            Box::new(Type::Uint(256)),
+        )
+    };
+
+    asserter.push_success(&_fee_history());
+    _execute(&mut env, "history = block.feeHistory(3)").await;
+    assert_eq!(
+        _recorded_params(&recording, "eth_feeHistory"),
+        serde_json::json!(["0x3", "latest", [10.0, 50.0, 90.0]])
+    );
+    _check_result(&mut env, "history.oldestBlock", Value::from(1u64)).await;
+    _check_result(&mut env, "history.baseFees", uint_array(&[80, 90, 95, 100])).await;
+    _check_result(
+        &mut env,
+        "history.rewards",
+        Value::Array(
+            vec![uint_array(&[5]), uint_array(&[0]), uint_array(&[7])],
+            Box::new(Type::Array(Box::new(Type::Uint(256)))),
+        ),
+    )
+    .await;
+
+    asserter.push_success(&_fee_history());
+    _execute(&mut env, "block.feeHistory(3, [12.5, 75])").await;
+    assert_eq!(
+        _recorded_params(&recording, "eth_feeHistory"),
+        serde_json::json!(["0x3", "latest", [12.5, 75.0]])
+    );
+    fs::remove_file(recording).unwrap();
+}
+
+const TYPED_DATA: &str = r#"{"types": {"EIP712Domain": [{"name": "name", "type": "string"}], "Mail": [{"name": "contents", "type": "string"}]}, "primaryType": "Mail", "domain": {"name": "Test"}, "message": {"contents": "hello"}}"#;
+
 async fn _execute(env: &mut Env, code: &str) -> Option<Value> {
     interpreter::evaluate_code(env, code).await.unwrap()
 }
@@ -142,10 +964,219 @@ async fn _check_result(env: &mut Env, code: &str, expected: Value) {
     assert_eq!(res, Some(expected));
 }
 
+fn _decode_tx(value: Option<Value>) -> TxEnvelope {
+    match value {
+        Some(Value::Bytes(raw)) => TxEnvelope::decode_2718(&mut raw.as_slice()).unwrap(),
+        v => panic!("expected a signed transaction, got {:?}", v),
+    }
+}
+
 fn _create_env() -> Env {
     let foundry_conf = foundry_config::load_config().unwrap();
     let config = Config::new(None, false, foundry_conf);
-    let mut env = Env::new(config);
+    let mut env = Env::new(config).unwrap();
     interpreter::load_builtins(&mut env);
     env
 }
+
+fn _create_mocked_env() -> (Env, Asserter) {
+    let foundry_conf = foundry_config::load_config().unwrap();
+    let config = Config::new(None, false, foundry_conf);
+    let asserter = Asserter::new();
+    let mut env = Env::with_client(config, RpcClient::mocked(asserter.clone()));
+    interpreter::load_builtins(&mut env);
+    (env, asserter)
+}
+
+/// Creates an environment answering requests from the asserter
+/// and recording them to a temporary file
+fn _create_recording_env(name: &str) -> (Env, Asserter, PathBuf) {
+    let foundry_conf = foundry_config::load_config().unwrap();
+    let config = Config::new(None, false, foundry_conf);
+    let asserter = Asserter::new();
+    let recording =
+        std::env::temp_dir().join(format!("eclair-{}-{}.json", name, std::process::id()));
+    let recorder = Arc::new(Recorder::new(recording.clone()));
+    let transport = RecordLayer::new(Some(recorder)).layer(MockTransport::new(asserter.clone()));
+    let mut env = Env::with_client(config, RpcClient::new(transport, true));
+    interpreter::load_builtins(&mut env);
+    (env, asserter, recording)
+}
+
+/// Returns the parameters of the last recorded request with the given method
+fn _recorded_params(recording: &Path, method: &str) -> serde_json::Value {
+    let calls: Vec<serde_json::Value> =
+        serde_json::from_str(&fs::read_to_string(recording).unwrap()).unwrap();
+    calls
+        .into_iter()
+        .rev()
+        .find(|call| call["method"] == method)
+        .unwrap_or_else(|| panic!("no {} request recorded", method))["params"]
+        .clone()
+}
+
+/// Encodes the result of a UniversalResolver `resolve` call for an `addr` query
+fn _universal_resolver_result(addr: Address, resolver: Address) -> Bytes {
+    (Bytes::from(addr.abi_encode()), resolver)
+        .abi_encode_params()
+        .into()
+}
+
+/// Starts an EIP-3668 gateway answering every request with the given data
+fn _start_gateway_server(data: Bytes) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap_or(0) > 0 && !line.trim_end().is_empty() {
+                line.clear();
+            }
+            let response = serde_json::json!({ "data": data }).to_string();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response.len(),
+                response
+            )
+            .unwrap();
+        }
+    });
+    url
+}
+
+/// Decodes the last transaction sent with `eth_sendRawTransaction`
+fn _last_sent_tx(recording: &Path) -> TxEnvelope {
+    let params = _recorded_params(recording, "eth_sendRawTransaction");
+    let raw: Bytes = serde_json::from_value(params[0].clone()).unwrap();
+    TxEnvelope::decode_2718(&mut raw.as_ref()).unwrap()
+}
+
+/// Fee history from which alloy estimates a max fee of twice the base fee
+/// plus the reward, and a priority fee of the reward (at least 1 wei)
+fn _estimation_history(base_fee: u128, reward: u128) -> FeeHistory {
+    FeeHistory {
+        oldest_block: 1,
+        base_fee_per_gas: vec![base_fee, base_fee],
+        gas_used_ratio: vec![0.5],
+        reward: Some(vec![vec![reward]]),
+        ..Default::default()
+    }
+}
+
+/// Fee history of three blocks, the last base fee being the one of the next block
+fn _fee_history() -> FeeHistory {
+    FeeHistory {
+        oldest_block: 1,
+        base_fee_per_gas: vec![80, 90, 95, 100],
+        gas_used_ratio: vec![0.5, 0.5, 0.5],
+        reward: Some(vec![vec![5], vec![0], vec![7]]),
+        ..Default::default()
+    }
+}
+
+/// Wraps a signed transaction as returned by `eth_getTransactionByHash`
+fn _rpc_tx(tx: TxEnvelope, from: Address, block_number: Option<u64>) -> RpcTransaction {
+    RpcTransaction {
+        inner: Recovered::new_unchecked(tx, from),
+        block_hash: block_number.map(|_| B256::ZERO),
+        block_number,
+        transaction_index: block_number.map(|_| 0),
+        effective_gas_price: None,
+    }
+}
+
+/// How the signer server answers `eth_signTransaction` requests
+#[derive(Clone, Copy)]
+enum SignedTxResponse {
+    /// The raw signed transaction
+    Raw,
+    /// An object with the raw signed transaction, as returned by geth
+    Object,
+    /// A signed transaction with a different nonce than the requested one
+    WrongNonce,
+}
+
+/// Starts a JSON-RPC server answering `eth_sign`, `eth_signTypedData_v4`
+/// and `eth_signTransaction` requests with the given key
+fn _start_signer_server(signer: PrivateKeySigner, tx_response: SignedTxResponse) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let signer = signer.clone();
+            std::thread::spawn(move || {
+                _serve_signer_requests(stream.unwrap(), &signer, tx_response)
+            });
+        }
+    });
+    url
+}
+
+fn _serve_signer_requests(
+    mut stream: TcpStream,
+    signer: &PrivateKeySigner,
+    tx_response: SignedTxResponse,
+) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    loop {
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+            match line.trim_end().split_once(':') {
+                Some((name, value)) if name.eq_ignore_ascii_case("content-length") => {
+                    content_length = value.trim().parse().unwrap();
+                }
+                None if line.trim_end().is_empty() => break,
+                _ => {}
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let result = match request["method"].as_str() {
+            Some("eth_sign") => {
+                let message = hex::decode(request["params"][1].as_str().unwrap()).unwrap();
+                let signature = signer.sign_message_sync(&message).unwrap();
+                serde_json::json!(hex::encode_prefixed(signature.as_bytes()))
+            }
+            Some("eth_signTypedData_v4") => {
+                let typed_data: TypedData =
+                    serde_json::from_value(request["params"][1].clone()).unwrap();
+                let signature = signer.sign_dynamic_typed_data_sync(&typed_data).unwrap();
+                serde_json::json!(hex::encode_prefixed(signature.as_bytes()))
+            }
+            Some("eth_signTransaction") => {
+                let mut tx_req: TransactionRequest =
+                    serde_json::from_value(request["params"][0].clone()).unwrap();
+                if let SignedTxResponse::WrongNonce = tx_response {
+                    tx_req.nonce = tx_req.nonce.map(|nonce| nonce + 1);
+                }
+                let mut tx = tx_req.build_typed_tx().unwrap();
+                let signature = signer.sign_transaction_sync(&mut tx).unwrap();
+                let raw = hex::encode_prefixed(tx.into_envelope(signature).encoded_2718());
+                match tx_response {
+                    SignedTxResponse::Object => {
+                        serde_json::json!({"raw": raw, "tx": request["params"][0]})
+                    }
+                    _ => serde_json::json!(raw),
+                }
+            }
+            _ => serde_json::Value::Null,
+        };
+        let response = serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": result})
+            .to_string();
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            response.len(),
+            response
+        )
+        .unwrap();
+    }
+}